@@ -0,0 +1,82 @@
+use ndarray::Array2;
+use pon::games::chooser::BestResponseEpsilonError;
+use pon::games::counting::ActionCountingProcess;
+use pon::games::game::{InitialAction, MatrixGame};
+use pon::process::network::Network;
+use pon::process::process::{NodeContext, Process};
+use pon::process::simulator::{Simulator, SimulatorConfig};
+use pon::process::state::State;
+use rand::SeedableRng;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use std::time::{Duration, Instant};
+
+/// Sequential step of `Simulator` as it was before double-buffered `State`:
+/// fresh vectors every step, with the same per-node bookkeeping
+fn allocating_steps<P: Process>(process: &P, network: &Network, steps: usize) -> Duration {
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(1);
+    let mut state = process.make_initial_state(&mut rng, network);
+    let mut action_counts = Array2::<u64>::zeros((network.node_count(), process.actions()));
+    let mut changed = 0;
+    let graph = network.graph();
+    let start = Instant::now();
+    for step in 1..=steps {
+        let last_actions = state.last_actions();
+        let node_states = state.node_states();
+        let signals: Vec<_> = node_states
+            .iter()
+            .zip(last_actions)
+            .map(|(node_state, action)| process.signal(node_state, *action))
+            .collect();
+        let (node_states, actions): (Vec<_>, Vec<_>) = node_states
+            .iter()
+            .zip(last_actions)
+            .enumerate()
+            .map(|(idx, (node_state, last_action))| {
+                let neighbors = graph
+                    .neighbors((idx as u32).into())
+                    .map(|other| signals[other.index()].clone());
+                let context = NodeContext::new(network, idx, step);
+                let (node_state, action) =
+                    process.node_step(&mut rng, &context, node_state, *last_action, neighbors);
+                action_counts[(idx, action)] += 1;
+                changed += (action != *last_action) as usize;
+                (node_state, action)
+            })
+            .unzip();
+        state = State::new(node_states, actions);
+    }
+    let elapsed = start.elapsed();
+    assert!(changed > 0 && action_counts.sum() == (steps * network.node_count()) as u64);
+    elapsed
+}
+
+/// Sequential steps of `Simulator`, which writes into back buffers that are swapped
+fn buffered_steps<P: Process>(process: &P, network: &Network, steps: usize) -> Duration {
+    let mut config = SimulatorConfig::new();
+    config.set_bootstrap_steps(steps + 1);
+    let mut simulator = Simulator::with_seed(&config, 1, network, process);
+    let start = Instant::now();
+    assert_eq!(simulator.run_until(steps), None);
+    start.elapsed()
+}
+
+pub fn main() {
+    let steps = 500;
+    let process = ActionCountingProcess::new(
+        MatrixGame::new([[1.0, 0.0], [0.0, 1.0]], InitialAction::Uniform),
+        BestResponseEpsilonError::new(0.1),
+    );
+    for size in [10, 100, 300] {
+        let network = Network::grid(size, size);
+        let allocating = allocating_steps(&process, &network, steps);
+        let buffered = buffered_steps(&process, &network, steps);
+        println!(
+            "nodes={} steps={} allocating={:?} buffered={:?} speedup={:.2}",
+            network.node_count(),
+            steps,
+            allocating,
+            buffered,
+            allocating.as_secs_f64() / buffered.as_secs_f64()
+        );
+    }
+}
@@ -1,6 +1,5 @@
-use ndarray::{Array, Axis};
+use ndarray::Array;
 use pon::env::streamer::Streamer;
-use pon::games::chooser::SamplingEpsilonError;
 use pon::games::chooser::SoftmaxSample;
 use pon::games::game::{InitialAction, MatrixGame};
 use pon::games::regret::RegretMatchingProcess;
@@ -18,6 +17,12 @@ pub fn main() {
     config.set_window_steps(20000);
     config.set_max_windows(500);
     config.set_termination_threshold(0.003);
+    let config = config
+        .update_from_args(std::env::args().skip(1))
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1)
+        });
 
     let payoffs = [[4.0, 1.0], [3.0, 2.0]];
     let streamer = Streamer::new(Path::new("./e1.data")).unwrap();
@@ -34,7 +39,7 @@ pub fn main() {
             let init_dist = init_dist as f32;
             let game = RegretMatchingProcess::new(
                 MatrixGame::new(payoffs, InitialAction::Distribution(FloatArray::from([init_dist, 1.0 - init_dist]))),
-                SoftmaxSample::default(),
+                SoftmaxSample,
             );
 
             [_replication = 0..1000]
@@ -44,7 +49,7 @@ pub fn main() {
             let report = simulator.report();
             let policy = report.avg_policy.into_raw_vec();
             //dbg!(&policy);
-            let result = json!({ "net": network.description(), "init_dist": init_dist, "policy": policy, "steps": report.steps});
+            let result = json!({ "net": network.description(), "init_dist": init_dist, "policy": policy, "steps": report.steps, "termination": report.termination, "seed": report.seed, "configuration": report.configuration});
             streamer.send(&result);
         }
 }
@@ -3,10 +3,11 @@ use pon::env::streamer::Streamer;
 use pon::games::chooser::SamplingEpsilonError;
 use pon::games::game::{InitialAction, MatrixGame};
 use pon::games::regret::RegretMatchingProcess;
+use pon::process::ensemble::Ensemble;
 use pon::process::network::Network;
-use pon::process::simulator::{Simulator, SimulatorConfig};
+use pon::process::simulator::SimulatorConfig;
 use pon::scan;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 use serde_json::json;
 use std::path::Path;
 
@@ -16,6 +17,12 @@ pub fn main() {
     config.set_window_steps(20000);
     config.set_max_windows(500);
     config.set_termination_threshold(0.003);
+    let config = config
+        .update_from_args(std::env::args().skip(1))
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1)
+        });
 
     let payoffs = [[4.0, 1.0], [3.0, 2.0]];
     let streamer = Streamer::new(Path::new("./part3.data")).unwrap();
@@ -32,13 +39,11 @@ pub fn main() {
             SamplingEpsilonError::new(epsilon),
         );
 
-        [_replication = 0..10]
-        let mut rng = thread_rng();
-        let mut simulator = Simulator::new(&config, Some(&mut rng), &network, &game);
-        simulator.run();
-        let report = simulator.report();
-        let policy = report.avg_policy.mean_axis(Axis(0)).unwrap().into_raw_vec();
-        let result = json!({ "net": network.description(), "epsilon": epsilon, "policy": policy, "steps": report.steps});
-        streamer.send(&result);
+        let ensemble = Ensemble::new(&config, &network, &game, 10, thread_rng().gen()).run();
+        ensemble.reports.iter().for_each(|report| {
+            let policy = report.avg_policy.mean_axis(Axis(0)).unwrap().into_raw_vec();
+            let result = json!({ "net": network.description(), "epsilon": epsilon, "policy": policy, "steps": report.steps, "termination": report.termination, "seed": report.seed, "configuration": report.configuration});
+            streamer.send(&result);
+        });
     }
 }
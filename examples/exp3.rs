@@ -17,6 +17,12 @@ pub fn main() {
     config.set_window_steps(20000);
     config.set_max_windows(500);
     config.set_termination_threshold(0.003);
+    let config = config
+        .update_from_args(std::env::args().skip(1))
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1)
+        });
 
     let streamer = Streamer::new(Path::new("./hunt.data")).unwrap();
     scan! {
@@ -31,7 +37,7 @@ pub fn main() {
 
         let game = ActionCountingProcess::new(
             MatrixGame::new(payoffs, InitialAction::Uniform),
-            SoftmaxSample::default(),
+            SoftmaxSample,
         );
 
         let mut rng = thread_rng();
@@ -43,7 +49,8 @@ pub fn main() {
             "net": network.description(),
             "stag_payoff": stag_payoff,
             "policy": policy,
-            "steps": report.steps});
+            "steps": report.steps, "termination": report.termination,
+            "seed": report.seed, "configuration": report.configuration});
         streamer.send(&result);
     }
 }
@@ -1,4 +1,3 @@
-use ndarray::Array;
 use ndarray::Axis;
 use pon::env::streamer::Streamer;
 use pon::games::chooser::SoftmaxSample;
@@ -12,7 +11,7 @@ use serde_json::json;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-fn scan_dir_for_graphs(path: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+fn scan_dir_for_graphs() -> Result<Vec<PathBuf>, std::io::Error> {
     Ok(fs::read_dir("../netprocess-data/v0.2/data-S")?
         .filter_map(Result::ok)
         .filter_map(|entry| {
@@ -31,13 +30,18 @@ fn scan_dir_for_graphs(path: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
 }
 
 pub fn main() {
-    let network_filenames =
-        scan_dir_for_graphs(Path::new("../netprocess-data/v0.2/data-L")).unwrap();
+    let network_filenames = scan_dir_for_graphs().unwrap();
     let mut config = SimulatorConfig::new();
     config.set_bootstrap_steps(20000);
     config.set_window_steps(20000);
     config.set_max_windows(1000);
     config.set_termination_threshold(0.003);
+    let config = config
+        .update_from_args(std::env::args().skip(1))
+        .unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            std::process::exit(1)
+        });
 
     let streamer = Streamer::new(Path::new("./hunt.data")).unwrap();
 
@@ -52,7 +56,7 @@ pub fn main() {
 
         let game = ActionCountingProcess::new(
             MatrixGame::new(payoffs, InitialAction::Uniform),
-            SoftmaxSample::default(),
+            SoftmaxSample,
         );
 
         let mut rng = thread_rng();
@@ -64,7 +68,8 @@ pub fn main() {
             "net": network.description(),
             "stag_payoff": stag_payoff,
             "policy": policy,
-            "steps": report.steps});
+            "steps": report.steps, "termination": report.termination,
+            "seed": report.seed, "configuration": report.configuration});
         streamer.send(&result);
     }
 }
@@ -0,0 +1,211 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, std::io::Error),
+    /// File is not a valid JSON or TOML document
+    Syntax(PathBuf, String),
+    /// Document does not match the expected structure, `path` points to the failing field
+    Field {
+        path: String,
+        message: String,
+    },
+    /// Malformed command line argument
+    Argument(String),
+    /// Values are well-formed but not meaningful
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(path, error) => write!(f, "cannot read {}: {}", path.display(), error),
+            ConfigError::Syntax(path, message) => {
+                write!(f, "cannot parse {}: {}", path.display(), message)
+            }
+            ConfigError::Field { path, message } => write!(f, "invalid `{}`: {}", path, message),
+            ConfigError::Argument(message) => write!(f, "invalid argument: {}", message),
+            ConfigError::Invalid(message) => write!(f, "invalid configuration: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Reads a JSON or TOML document (chosen by the `.toml` extension) into a JSON value
+pub fn read_document(path: &Path) -> Result<Value, ConfigError> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| ConfigError::Io(path.to_path_buf(), e))?;
+    let is_toml = path.extension().is_some_and(|ext| ext == "toml");
+    if is_toml {
+        toml::from_str(&content).map_err(|e| ConfigError::Syntax(path.to_path_buf(), e.to_string()))
+    } else {
+        serde_json::from_str(&content)
+            .map_err(|e| ConfigError::Syntax(path.to_path_buf(), e.to_string()))
+    }
+}
+
+/// Converts a document into `T`; errors carry the path of the failing field
+pub fn from_document<T: DeserializeOwned>(document: Value) -> Result<T, ConfigError> {
+    serde_path_to_error::deserialize(document).map_err(|e| ConfigError::Field {
+        path: e.path().to_string(),
+        message: e.inner().to_string(),
+    })
+}
+
+/// Applies `key.subkey=value` to a document. The value is parsed as JSON,
+/// anything that is not valid JSON is taken as a string.
+pub fn apply_override(document: &mut Value, assignment: &str) -> Result<(), ConfigError> {
+    let (key, value) = assignment.split_once('=').ok_or_else(|| {
+        ConfigError::Argument(format!("expected key=value, got `{}`", assignment))
+    })?;
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    let mut target = document;
+    for part in key.split('.') {
+        if part.is_empty() {
+            return Err(ConfigError::Argument(format!("invalid key `{}`", key)));
+        }
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+        target = target
+            .as_object_mut()
+            .unwrap()
+            .entry(part)
+            .or_insert(Value::Null);
+    }
+    *target = value;
+    Ok(())
+}
+
+fn merge(target: &mut Value, source: Value) {
+    match (target, source) {
+        (Value::Object(target), Value::Object(source)) => {
+            for (key, value) in source {
+                match target.get_mut(&key) {
+                    Some(t) => merge(t, value),
+                    None => {
+                        target.insert(key, value);
+                    }
+                }
+            }
+        }
+        (target, source) => *target = source,
+    }
+}
+
+/// Loads a configuration file and applies `key=value` overrides
+pub fn load_config<T: DeserializeOwned>(
+    path: &Path,
+    overrides: &[String],
+) -> Result<T, ConfigError> {
+    let mut document = read_document(path)?;
+    for assignment in overrides {
+        apply_override(&mut document, assignment)?;
+    }
+    from_document(document)
+}
+
+/// Updates `base` from command line arguments: `--config <file>` merges a JSON/TOML file
+/// into it, any other argument is a `key=value` override applied afterwards.
+pub fn update_from_args<T: Serialize + DeserializeOwned>(
+    base: &T,
+    args: impl IntoIterator<Item = String>,
+) -> Result<T, ConfigError> {
+    let mut document = serde_json::to_value(base).unwrap();
+    let mut overrides = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            let path = args
+                .next()
+                .ok_or_else(|| ConfigError::Argument("--config requires a path".to_string()))?;
+            merge(&mut document, read_document(Path::new(&path))?);
+        } else {
+            overrides.push(arg);
+        }
+    }
+    for assignment in &overrides {
+        apply_override(&mut document, assignment)?;
+    }
+    from_document(document)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::env::config::{load_config, update_from_args, ConfigError};
+    use crate::process::simulator::SimulatorConfig;
+    use std::path::Path;
+
+    fn write(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("pon-config-{}", name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_load_toml_and_json() {
+        let path = write(
+            "a.toml",
+            "window_steps = 50\nparallel = true\n[trace_filter]\nmode = \"Delta\"\n",
+        );
+        let config: SimulatorConfig = load_config(&path, &[]).unwrap();
+        assert!(config.is_parallel());
+        let path = write("a.json", r#"{"window_steps": 50, "convergence": "L1"}"#);
+        let config = SimulatorConfig::load(&path, &["max_windows=7".to_string()]).unwrap();
+        assert!(!config.is_parallel());
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value["window_steps"], 50);
+        assert_eq!(value["max_windows"], 7);
+        assert_eq!(value["convergence"], "L1");
+    }
+
+    #[test]
+    fn test_field_errors() {
+        let path = write("b.json", r#"{"trace_filter": {"nodes": [1, "x"]}}"#);
+        match load_config::<SimulatorConfig>(&path, &[]) {
+            Err(ConfigError::Field { path, .. }) => assert_eq!(path, "trace_filter.nodes[1]"),
+            _ => panic!("expected field error"),
+        }
+        let path = write("c.json", r#"{"window_step": 10}"#);
+        assert!(matches!(
+            load_config::<SimulatorConfig>(&path, &[]),
+            Err(ConfigError::Field { .. })
+        ));
+        let path = write("d.json", r#"{"window_steps": 0}"#);
+        assert!(matches!(
+            SimulatorConfig::load(&path, &[]),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            SimulatorConfig::load(Path::new("/nonexistent/pon.json"), &[]),
+            Err(ConfigError::Io(..))
+        ));
+    }
+
+    #[test]
+    fn test_update_from_args() {
+        let mut base = SimulatorConfig::new();
+        base.set_window_steps(123);
+        let path = write("e.toml", "max_windows = 9\n");
+        let args = [
+            "--config",
+            path.to_str().unwrap(),
+            "parallel=true",
+            "trace_filter.nodes=[0,2]",
+            "trace_path=out.trace",
+        ];
+        let config = update_from_args(&base, args.iter().map(|s| s.to_string())).unwrap();
+        let value = serde_json::to_value(&config).unwrap();
+        assert_eq!(value["window_steps"], 123);
+        assert_eq!(value["max_windows"], 9);
+        assert_eq!(value["parallel"], true);
+        assert_eq!(value["trace_filter"]["nodes"], serde_json::json!([0, 2]));
+        assert_eq!(value["trace_path"], "out.trace");
+        assert!(update_from_args(&base, ["novalue".to_string()]).is_err());
+    }
+}
@@ -1,2 +1,3 @@
+pub mod config;
 pub mod scan;
 pub mod streamer;
@@ -0,0 +1,315 @@
+use crate::games::game::{payoff_matrix_serde, ActionId, Game, InitialAction};
+use crate::process::fixarray::{FixArray, FloatArray, IntArray};
+use crate::process::intervention::NodeSelection;
+use crate::process::network::Network;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Role {
+    #[default]
+    Row,
+    Column,
+}
+
+/// Asymmetric two-role game. A row node playing `i` against a column node playing `j`
+/// receives `row_payoffs[i][j]` and the column node receives `column_payoffs[i][j]`.
+/// Nodes play only against neighbors of the other role; edges between nodes
+/// of the same role carry no payoff.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BimatrixGame<const ACTIONS: usize> {
+    #[serde(with = "payoff_matrix_serde")]
+    row_payoffs: [[f32; ACTIONS]; ACTIONS],
+    #[serde(with = "payoff_matrix_serde")]
+    column_payoffs: [[f32; ACTIONS]; ACTIONS],
+    initial_action: InitialAction<ACTIONS>,
+    /// Nodes with the column role, all other nodes have the row role
+    column_nodes: NodeSelection,
+}
+
+impl<const ACTIONS: usize> BimatrixGame<ACTIONS> {
+    pub fn new(
+        row_payoffs: [[f32; ACTIONS]; ACTIONS],
+        column_payoffs: [[f32; ACTIONS]; ACTIONS],
+        initial_action: InitialAction<ACTIONS>,
+        column_nodes: NodeSelection,
+    ) -> Self {
+        assert!(ACTIONS > 0);
+        if let Err(message) = initial_action.validate() {
+            panic!("{}", message);
+        }
+        BimatrixGame {
+            row_payoffs,
+            column_payoffs,
+            initial_action,
+            column_nodes,
+        }
+    }
+
+    /// Payoff of a node with `role` playing `action` against an opponent playing `opponent_action`
+    #[inline(always)]
+    fn payoff(&self, role: Role, action: ActionId, opponent_action: ActionId) -> f32 {
+        match role {
+            Role::Row => self.row_payoffs[action][opponent_action],
+            Role::Column => self.column_payoffs[opponent_action][action],
+        }
+    }
+}
+
+impl<const ACTIONS: usize> Game for BimatrixGame<ACTIONS> {
+    type RoleT = Role;
+    type SignalT = (Role, ActionId);
+    type PayoffsT = FloatArray<ACTIONS>;
+    type CountsT = IntArray<ACTIONS>;
+
+    fn actions(&self) -> usize {
+        ACTIONS
+    }
+
+    fn validate(&self, network: &Network) -> Result<(), String> {
+        self.initial_action.validate()?;
+        self.column_nodes
+            .validate(network)
+            .map_err(|message| format!("column_nodes: {}", message))
+    }
+
+    fn make_roles(&self, rng: &mut impl rand::Rng, network: &Network) -> Vec<Role> {
+        let mut roles = vec![Role::Row; network.node_count()];
+        for node in self.column_nodes.resolve(network, rng) {
+            roles[node] = Role::Column;
+        }
+        roles
+    }
+
+    fn make_initial_action(&self, rng: &mut impl rand::Rng) -> ActionId {
+        match &self.initial_action {
+            InitialAction::Const(action) => *action,
+            InitialAction::Uniform => rng.gen_range(0..ACTIONS),
+            InitialAction::Distribution(a) => a.sample_index(rng),
+        }
+    }
+
+    fn signal(&self, role: Role, action: ActionId) -> (Role, ActionId) {
+        (role, action)
+    }
+
+    fn opponent_action(&self, role: Role, signal: (Role, ActionId)) -> Option<ActionId> {
+        let (other_role, action) = signal;
+        (other_role != role).then_some(action)
+    }
+
+    fn payoffs_sums(
+        &self,
+        role: Role,
+        opponent_actions: impl Iterator<Item = ActionId>,
+    ) -> FloatArray<ACTIONS> {
+        let mut payoffs = [0f32; ACTIONS];
+        for opponent_action in opponent_actions {
+            for (action, p) in payoffs.iter_mut().enumerate() {
+                *p += self.payoff(role, action, opponent_action);
+            }
+        }
+        FixArray::from(payoffs)
+    }
+
+    fn expect_payoffs(
+        &self,
+        role: Role,
+        opponent_strategy: FloatArray<ACTIONS>,
+    ) -> FloatArray<ACTIONS> {
+        let mut payoffs = [0f32; ACTIONS];
+        for (action, p) in payoffs.iter_mut().enumerate() {
+            for opponent_action in 0..ACTIONS {
+                *p += opponent_strategy.get(opponent_action)
+                    * self.payoff(role, action, opponent_action);
+            }
+        }
+        FixArray::from(payoffs)
+    }
+
+    fn role_names(&self) -> Vec<String> {
+        vec!["row".to_string(), "column".to_string()]
+    }
+
+    fn role_index(&self, role: Role) -> usize {
+        role as usize
+    }
+
+    /// Payoff interventions change the payoffs of the row role
+    fn payoff_matrix(&self) -> Vec<Vec<f32>> {
+        self.row_payoffs.iter().map(|row| row.to_vec()).collect()
+    }
+
+    fn set_payoff_matrix(&mut self, rows: &[Vec<f32>]) -> Result<(), String> {
+        self.row_payoffs = payoff_matrix_serde::from_rows(rows)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::bimatrix::{BimatrixGame, Role};
+    use crate::games::chooser::{BestResponseEpsilonError, DirectChooser};
+    use crate::games::counting::ActionCountingProcess;
+    use crate::games::game::{Game, InitialAction};
+    use crate::games::regret::RegretMatchingProcess;
+    use crate::process::intervention::{Intervention, InterventionKind, NodeSelection};
+    use crate::process::network::Network;
+    use crate::process::simulator::{Simulator, SimulatorConfig};
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+    use serde_json::json;
+
+    /// Row nodes prefer action 0 and column nodes action 1, whatever the opponent plays
+    fn dominant_game(column_nodes: NodeSelection) -> BimatrixGame<2> {
+        BimatrixGame::new(
+            [[1.0, 1.0], [0.0, 0.0]],
+            [[0.0, 1.0], [0.0, 1.0]],
+            InitialAction::Uniform,
+            column_nodes,
+        )
+    }
+
+    #[test]
+    fn test_role_payoffs() {
+        let game = BimatrixGame::new(
+            [[1.0, 2.0], [3.0, 4.0]],
+            [[5.0, 6.0], [7.0, 8.0]],
+            InitialAction::Const(0),
+            NodeSelection::Bipartite,
+        );
+        let sums = game.payoffs_sums(Role::Row, [0, 1, 1].into_iter());
+        assert_eq!(sums.as_slice(), &[5.0, 11.0]);
+        let sums = game.payoffs_sums(Role::Column, [0, 1, 1].into_iter());
+        assert_eq!(sums.as_slice(), &[19.0, 22.0]);
+        assert_eq!(game.opponent_action(Role::Row, (Role::Column, 1)), Some(1));
+        assert_eq!(game.opponent_action(Role::Row, (Role::Row, 1)), None);
+    }
+
+    #[test]
+    fn test_bipartite_roles() {
+        let process = ActionCountingProcess::new(
+            dominant_game(NodeSelection::Bipartite),
+            BestResponseEpsilonError::new(0.0),
+        );
+        let network = Network::grid(4, 4);
+        let config = SimulatorConfig::new();
+        let mut simulator = Simulator::new(&config, None, &network, &process);
+        simulator.run();
+        let report = simulator.report();
+        assert_eq!(report.groups[0].name, "row");
+        assert_eq!(report.groups[0].nodes, 8);
+        assert_eq!(report.groups[0].policy, [1.0, 0.0]);
+        assert_eq!(report.groups[1].name, "column");
+        assert_eq!(report.groups[1].nodes, 8);
+        assert_eq!(report.groups[1].policy, [0.0, 1.0]);
+        assert_eq!(
+            report.configuration.process["matrix_game"]["column_nodes"],
+            "Bipartite"
+        );
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let process = ActionCountingProcess::new(
+            dominant_game(NodeSelection::Bipartite),
+            BestResponseEpsilonError::new(0.0),
+        );
+        let triangle = Network::random(&mut SmallRng::seed_from_u64(0), 3, 1.0);
+        let config = SimulatorConfig::new();
+        let error = Simulator::try_with_seed(&config, 0, &triangle, &process)
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("column_nodes: network is not bipartite"));
+
+        let network = Network::grid(2, 2);
+        let mut config = SimulatorConfig::new();
+        config.add_intervention(Intervention::new(
+            1,
+            InterventionKind::SetPayoffs(vec![vec![0.0; 3]; 3]),
+        ));
+        let error = Simulator::try_with_seed(&config, 0, &network, &process)
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("payoff matrix has to be 2x2"));
+
+        // The constructor rejects this, but deserialized games are only checked here
+        let mut configuration =
+            serde_json::to_value(dominant_game(NodeSelection::Bipartite)).unwrap();
+        configuration["initial_action"] = json!({"Const": 2});
+        let process = ActionCountingProcess::new(
+            serde_json::from_value::<BimatrixGame<2>>(configuration).unwrap(),
+            BestResponseEpsilonError::new(0.0),
+        );
+        let error = Simulator::try_with_seed(&SimulatorConfig::new(), 0, &network, &process)
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("process: initial action 2 is out of range"));
+    }
+
+    #[test]
+    fn test_payoff_intervention_changes_row_payoffs() {
+        let mut game = dominant_game(NodeSelection::Bipartite);
+        assert!(game.set_payoff_matrix(&[vec![0.0, 1.0]]).is_err());
+        game.set_payoff_matrix(&[vec![0.0, 0.0], vec![1.0, 1.0]])
+            .unwrap();
+        assert_eq!(game.payoff_matrix(), [[0.0, 0.0], [1.0, 1.0]]);
+        let sums = game.payoffs_sums(Role::Row, [0].into_iter());
+        assert_eq!(sums.as_slice(), &[0.0, 1.0]);
+        let sums = game.payoffs_sums(Role::Column, [0].into_iter());
+        assert_eq!(sums.as_slice(), &[0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_battle_of_sexes() {
+        let game = BimatrixGame::new(
+            [[2.0, 0.0], [0.0, 1.0]],
+            [[1.0, 0.0], [0.0, 2.0]],
+            InitialAction::Uniform,
+            NodeSelection::Nodes(vec![1]),
+        );
+        let process = RegretMatchingProcess::new(game, DirectChooser::new());
+        let network = Network::line(2);
+        let mut config = SimulatorConfig::new();
+        config.set_termination_threshold(0.001);
+        for seed in 0..5 {
+            let mut simulator = Simulator::with_seed(&config, seed, &network, &process);
+            simulator.run();
+            let report = simulator.report();
+            assert!(report.converged());
+            // Both players coordinate on the same action
+            let actions = simulator.state().last_actions();
+            assert_eq!(actions[0], actions[1]);
+        }
+    }
+
+    #[test]
+    fn test_same_role_edges_are_ignored() {
+        let process = ActionCountingProcess::new(
+            dominant_game(NodeSelection::Attribute {
+                name: "role".to_string(),
+                value: json!("column"),
+            }),
+            BestResponseEpsilonError::new(0.0),
+        );
+        let mut network = Network::line(3);
+        network.set_node_attribute("role", vec![json!("row"), json!("row"), json!("column")]);
+        let mut config = SimulatorConfig::new();
+        config.set_report_details(true);
+        let mut simulator = Simulator::new(&config, None, &network, &process);
+        simulator.run();
+        let report = simulator.report();
+        let states = report.details.unwrap().node_states;
+        assert_eq!(states[0]["role"], "Row");
+        assert_eq!(states[0]["action_counts"], json!([0, 0]));
+        assert_eq!(states[2]["role"], "Column");
+        let counts = &states[1]["action_counts"];
+        assert_eq!(
+            counts[0].as_u64().unwrap() + counts[1].as_u64().unwrap(),
+            report.steps as u64
+        );
+    }
+}
@@ -1,75 +1,275 @@
 use crate::games::game::ActionId;
-use crate::process::fixarray::FloatArray;
+use crate::process::fixarray::WeightArray;
 use rand::distributions::Bernoulli;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-pub trait ActionChooser<const ACTIONS: usize> {
-    fn choose_action(&self, rng: &mut impl Rng, payoffs: FloatArray<ACTIONS>) -> ActionId;
+/// Chooses an action from payoffs given as `FloatArray` or `FloatDynArray`
+pub trait ActionChooser: Sync + Clone {
+    fn choose_action<PayoffsT: WeightArray>(
+        &self,
+        rng: &mut impl Rng,
+        payoffs: PayoffsT,
+    ) -> ActionId;
+
+    fn configuration(&self) -> Value;
+}
+
+/// Serde form of choosers with an error rate, the same as their `configuration()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct EpsilonParams {
+    #[serde(default)]
+    chooser: Option<String>,
+    epsilon: f32,
+}
+
+/// Serde form of choosers without parameters, the same as their `configuration()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NoParams {
+    #[serde(default)]
+    chooser: Option<String>,
 }
 
+/// Fails if a stored configuration names a different chooser; the name is optional
+fn check_chooser(chooser: &Option<String>, expected: &str) -> Result<(), String> {
+    match chooser {
+        Some(name) if name != expected => {
+            Err(format!("chooser {} cannot be read as {}", name, expected))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn error_distribution(epsilon: f32) -> Result<Bernoulli, String> {
+    Bernoulli::new(epsilon as f64).map_err(|_| format!("epsilon {} is not in [0, 1]", epsilon))
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(into = "NoParams", try_from = "NoParams")]
 pub struct DirectChooser;
 
+impl TryFrom<NoParams> for DirectChooser {
+    type Error = String;
+
+    fn try_from(params: NoParams) -> Result<Self, String> {
+        check_chooser(&params.chooser, "direct")?;
+        Ok(DirectChooser)
+    }
+}
+
+impl From<DirectChooser> for NoParams {
+    fn from(_chooser: DirectChooser) -> Self {
+        NoParams {
+            chooser: Some("direct".to_string()),
+        }
+    }
+}
+
 impl DirectChooser {
     pub fn new() -> Self {
         DirectChooser {}
     }
 }
 
-impl<const ACTIONS: usize> ActionChooser<ACTIONS> for DirectChooser {
-    fn choose_action(&self, rng: &mut impl Rng, payoffs: FloatArray<ACTIONS>) -> ActionId {
+impl ActionChooser for DirectChooser {
+    fn choose_action<PayoffsT: WeightArray>(
+        &self,
+        rng: &mut impl Rng,
+        payoffs: PayoffsT,
+    ) -> ActionId {
         payoffs.sample_index(rng)
     }
+
+    fn configuration(&self) -> Value {
+        json!({ "chooser": "direct" })
+    }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "EpsilonParams", try_from = "EpsilonParams")]
 pub struct SamplingEpsilonError {
+    epsilon: f32,
     error_distribution: Bernoulli,
 }
 
 impl SamplingEpsilonError {
+    /// Panics if `epsilon` is not in [0, 1]
     pub fn new(epsilon: f32) -> Self {
         SamplingEpsilonError {
-            error_distribution: Bernoulli::new(epsilon as f64).unwrap(),
+            epsilon,
+            error_distribution: error_distribution(epsilon).unwrap(),
         }
     }
 }
 
-impl<const ACTIONS: usize> ActionChooser<ACTIONS> for SamplingEpsilonError {
-    fn choose_action(&self, rng: &mut impl Rng, payoffs: FloatArray<ACTIONS>) -> ActionId {
+impl TryFrom<EpsilonParams> for SamplingEpsilonError {
+    type Error = String;
+
+    fn try_from(params: EpsilonParams) -> Result<Self, String> {
+        check_chooser(&params.chooser, "sampling_eps")?;
+        Ok(SamplingEpsilonError {
+            epsilon: params.epsilon,
+            error_distribution: error_distribution(params.epsilon)?,
+        })
+    }
+}
+
+impl From<SamplingEpsilonError> for EpsilonParams {
+    fn from(chooser: SamplingEpsilonError) -> Self {
+        EpsilonParams {
+            chooser: Some("sampling_eps".to_string()),
+            epsilon: chooser.epsilon,
+        }
+    }
+}
+
+impl ActionChooser for SamplingEpsilonError {
+    fn choose_action<PayoffsT: WeightArray>(
+        &self,
+        rng: &mut impl Rng,
+        payoffs: PayoffsT,
+    ) -> ActionId {
         if rng.sample(self.error_distribution) {
-            rng.gen_range(0..ACTIONS)
+            rng.gen_range(0..payoffs.size())
         } else {
             payoffs.sample_index(rng)
         }
     }
+
+    fn configuration(&self) -> Value {
+        json!({ "chooser": "sampling_eps", "epsilon": self.epsilon })
+    }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(into = "EpsilonParams", try_from = "EpsilonParams")]
 pub struct BestResponseEpsilonError {
+    epsilon: f32,
     error_distribution: Bernoulli,
 }
 
 impl BestResponseEpsilonError {
+    /// Panics if `epsilon` is not in [0, 1]
     pub fn new(epsilon: f32) -> Self {
         BestResponseEpsilonError {
-            error_distribution: Bernoulli::new(epsilon as f64).unwrap(),
+            epsilon,
+            error_distribution: error_distribution(epsilon).unwrap(),
         }
     }
 }
 
-impl<const ACTIONS: usize> ActionChooser<ACTIONS> for BestResponseEpsilonError {
-    fn choose_action(&self, rng: &mut impl Rng, payoffs: FloatArray<ACTIONS>) -> ActionId {
+impl TryFrom<EpsilonParams> for BestResponseEpsilonError {
+    type Error = String;
+
+    fn try_from(params: EpsilonParams) -> Result<Self, String> {
+        check_chooser(&params.chooser, "best_response_eps")?;
+        Ok(BestResponseEpsilonError {
+            epsilon: params.epsilon,
+            error_distribution: error_distribution(params.epsilon)?,
+        })
+    }
+}
+
+impl From<BestResponseEpsilonError> for EpsilonParams {
+    fn from(chooser: BestResponseEpsilonError) -> Self {
+        EpsilonParams {
+            chooser: Some("best_response_eps".to_string()),
+            epsilon: chooser.epsilon,
+        }
+    }
+}
+
+impl ActionChooser for BestResponseEpsilonError {
+    fn choose_action<PayoffsT: WeightArray>(
+        &self,
+        rng: &mut impl Rng,
+        payoffs: PayoffsT,
+    ) -> ActionId {
         if rng.sample(self.error_distribution) {
-            rng.gen_range(0..ACTIONS)
+            rng.gen_range(0..payoffs.size())
         } else {
             payoffs.argmax()
         }
     }
+
+    fn configuration(&self) -> Value {
+        json!({ "chooser": "best_response_eps", "epsilon": self.epsilon })
+    }
 }
 
-#[derive(Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(into = "NoParams", try_from = "NoParams")]
 pub struct SoftmaxSample;
 
-impl<const ACTIONS: usize> ActionChooser<ACTIONS> for SoftmaxSample {
-    fn choose_action(&self, rng: &mut impl Rng, payoffs: FloatArray<ACTIONS>) -> ActionId {
+impl TryFrom<NoParams> for SoftmaxSample {
+    type Error = String;
+
+    fn try_from(params: NoParams) -> Result<Self, String> {
+        check_chooser(&params.chooser, "softmax")?;
+        Ok(SoftmaxSample)
+    }
+}
+
+impl From<SoftmaxSample> for NoParams {
+    fn from(_chooser: SoftmaxSample) -> Self {
+        NoParams {
+            chooser: Some("softmax".to_string()),
+        }
+    }
+}
+
+impl ActionChooser for SoftmaxSample {
+    fn choose_action<PayoffsT: WeightArray>(
+        &self,
+        rng: &mut impl Rng,
+        payoffs: PayoffsT,
+    ) -> ActionId {
         payoffs.exp().sample_index(rng)
     }
+
+    fn configuration(&self) -> Value {
+        json!({ "chooser": "softmax" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::chooser::{
+        ActionChooser, BestResponseEpsilonError, DirectChooser, SamplingEpsilonError, SoftmaxSample,
+    };
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::json;
+
+    fn check_round_trip<ChooserT: ActionChooser + Serialize + DeserializeOwned>(chooser: ChooserT) {
+        let configuration = chooser.configuration();
+        let restored: ChooserT = serde_json::from_value(configuration.clone()).unwrap();
+        assert_eq!(restored.configuration(), configuration);
+        let serialized = serde_json::to_value(&chooser).unwrap();
+        let restored: ChooserT = serde_json::from_value(serialized).unwrap();
+        assert_eq!(restored.configuration(), configuration);
+    }
+
+    #[test]
+    fn test_configuration_round_trip() {
+        check_round_trip(DirectChooser::new());
+        check_round_trip(SoftmaxSample);
+        check_round_trip(SamplingEpsilonError::new(0.25));
+        check_round_trip(BestResponseEpsilonError::new(0.1));
+    }
+
+    #[test]
+    fn test_invalid_configuration() {
+        let sampling = SamplingEpsilonError::new(0.25).configuration();
+        let error = serde_json::from_value::<BestResponseEpsilonError>(sampling).unwrap_err();
+        assert!(error.to_string().contains("chooser sampling_eps"));
+        assert!(serde_json::from_value::<DirectChooser>(SoftmaxSample.configuration()).is_err());
+        for epsilon in [1.5, -0.1] {
+            let configuration = json!({"chooser": "best_response_eps", "epsilon": epsilon});
+            assert!(serde_json::from_value::<BestResponseEpsilonError>(configuration).is_err());
+        }
+        let untagged = json!({"epsilon": 0.5});
+        assert!(serde_json::from_value::<SamplingEpsilonError>(untagged).is_ok());
+    }
 }
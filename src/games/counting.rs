@@ -1,28 +1,28 @@
 use crate::games::chooser::ActionChooser;
-use crate::games::game::{ActionId, MatrixGame};
-use crate::process::fixarray::IntArray;
+use crate::games::game::{is_unit_role, ActionId, Game};
+use crate::process::fixarray::{CountArray, PayoffArray};
 use crate::process::network::Network;
-use crate::process::process::Process;
+use crate::process::process::{NodeContext, Process};
 use crate::process::state::State;
 use rand::Rng;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-#[derive(Debug, Serialize)]
-pub struct PlayerState<const ACTIONS: usize> {
-    action_counts: IntArray<ACTIONS>,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerState<CountsT, RoleT = ()> {
+    action_counts: CountsT,
+    #[serde(default, skip_serializing_if = "is_unit_role")]
+    role: RoleT,
 }
 
-#[derive(Debug)]
-pub struct ActionCountingProcess<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>> {
-    game: MatrixGame<ACTIONS>,
+#[derive(Debug, Clone)]
+pub struct ActionCountingProcess<ActionChooserT: ActionChooser, GameT: Game> {
+    game: GameT,
     action_chooser: ActionChooserT,
 }
 
-impl<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>>
-    ActionCountingProcess<ACTIONS, ActionChooserT>
-{
-    pub fn new(game: MatrixGame<ACTIONS>, action_chooser: ActionChooserT) -> Self {
+impl<ActionChooserT: ActionChooser, GameT: Game> ActionCountingProcess<ActionChooserT, GameT> {
+    pub fn new(game: GameT, action_chooser: ActionChooserT) -> Self {
         ActionCountingProcess {
             game,
             action_chooser,
@@ -30,40 +30,63 @@ impl<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>>
     }
 }
 
-impl<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>> Process
-    for ActionCountingProcess<ACTIONS, ActionChooserT>
+impl<ActionChooserT: ActionChooser, GameT: Game> Process
+    for ActionCountingProcess<ActionChooserT, GameT>
 {
-    type NodeStateT = PlayerState<ACTIONS>;
-    const ACTIONS: usize = ACTIONS;
+    type NodeStateT = PlayerState<GameT::CountsT, GameT::RoleT>;
+    type SignalT = GameT::SignalT;
+
+    fn actions(&self) -> usize {
+        self.game.actions()
+    }
+
+    fn validate(&self, network: &Network) -> Result<(), String> {
+        self.game.validate(network)
+    }
 
     fn make_initial_state(&self, rng: &mut impl Rng, network: &Network) -> State<Self> {
-        State::new_by(network, || {
-            (
-                PlayerState::<ACTIONS> {
-                    action_counts: IntArray::default(),
-                },
-                self.game.make_initial_action(rng),
-            )
-        })
+        let roles = self.game.make_roles(rng, network);
+        let (node_states, actions) = roles
+            .into_iter()
+            .map(|role| {
+                (
+                    PlayerState {
+                        action_counts: GameT::CountsT::zeros(self.game.actions()),
+                        role,
+                    },
+                    self.game.make_initial_action(rng),
+                )
+            })
+            .unzip();
+        State::new(node_states, actions)
+    }
+
+    fn signal(&self, node_state: &Self::NodeStateT, action: ActionId) -> GameT::SignalT {
+        self.game.signal(node_state.role, action)
     }
 
     fn node_step(
         &self,
         rng: &mut impl Rng,
-        node_state: &PlayerState<ACTIONS>,
+        _context: &NodeContext,
+        node_state: &Self::NodeStateT,
         _last_action: ActionId,
-        neighbors: impl Iterator<Item = ActionId>,
-    ) -> (PlayerState<ACTIONS>, ActionId) {
+        neighbors: impl Iterator<Item = GameT::SignalT>,
+    ) -> (Self::NodeStateT, ActionId) {
+        let role = node_state.role;
         let mut counts = node_state.action_counts.clone();
-        for action in neighbors {
-            *counts.get_mut(action) += 1;
+        for signal in neighbors {
+            if let Some(action) = self.game.opponent_action(role, signal) {
+                *counts.get_mut(action) += 1;
+            }
         }
         let probs = counts.as_float().normalize();
-        let payoffs = self.game.expect_payoffs(probs);
+        let payoffs = self.game.expect_payoffs(role, probs);
         let action = self.action_chooser.choose_action(rng, payoffs);
         (
             PlayerState {
                 action_counts: counts,
+                role,
             },
             action,
         )
@@ -72,16 +95,54 @@ impl<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>> Process
     fn configuration(&self) -> Value {
         json!({
             "game": "br",
+            "matrix_game": self.game,
+            "chooser": self.action_chooser.configuration(),
         })
     }
+
+    fn groups(&self) -> Vec<String> {
+        self.game.role_names()
+    }
+
+    fn node_group(&self, node_state: &Self::NodeStateT) -> usize {
+        self.game.role_index(node_state.role)
+    }
+
+    fn payoff_matrix(&self) -> Option<Vec<Vec<f32>>> {
+        Some(self.game.payoff_matrix())
+    }
+
+    fn set_payoff_matrix(&mut self, payoff_matrix: &[Vec<f32>]) {
+        self.game
+            .set_payoff_matrix(payoff_matrix)
+            .expect("payoff matrix size is checked by SimulatorConfig::validate_for");
+    }
+
+    fn perturb_node_state(
+        &self,
+        rng: &mut impl Rng,
+        node_state: &mut Self::NodeStateT,
+        strength: f32,
+    ) {
+        // Counts are scaled and rounded randomly, so they are kept unbiased
+        let keep = 1.0 - strength.clamp(0.0, 1.0) as f64;
+        for action in 0..self.game.actions() {
+            let count = node_state.action_counts.get_mut(action);
+            let scaled = *count as f64 * keep;
+            *count = scaled as u32 + rng.gen_bool(scaled.fract()) as u32;
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::games::chooser::BestResponseEpsilonError;
-    use crate::games::counting::ActionCountingProcess;
-    use crate::games::game::{InitialAction, MatrixGame};
+    use crate::games::bimatrix::Role;
+    use crate::games::chooser::{BestResponseEpsilonError, SoftmaxSample};
+    use crate::games::counting::{ActionCountingProcess, PlayerState};
+    use crate::games::game::{DynMatrixGame, InitialAction, MatrixGame};
+    use crate::process::fixarray::IntArray;
     use crate::process::network::Network;
+    use crate::process::process::Process;
     use crate::process::simulator::{Simulator, SimulatorConfig};
     use approx::assert_abs_diff_eq;
     use ndarray::Axis;
@@ -120,8 +181,8 @@ mod tests {
         let mut simulator = Simulator::new(&config, None, &network, &game);
 
         assert_eq!(simulator.state().node_states().len(), 25);
-        assert!(simulator.state().last_actions().iter().any(|a| *a == 0));
-        assert!(simulator.state().last_actions().iter().any(|a| *a == 1));
+        assert!(simulator.state().last_actions().contains(&0));
+        assert!(simulator.state().last_actions().contains(&1));
 
         simulator.run();
 
@@ -141,6 +202,101 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_dyn_game_convergence() {
+        let game = ActionCountingProcess::new(
+            DynMatrixGame::from(MatrixGame::new(
+                [[0.0, 0.0], [0.0, 1.0]],
+                InitialAction::Uniform,
+            )),
+            BestResponseEpsilonError::new(0.1),
+        );
+        assert_eq!(game.actions(), 2);
+        let network = Network::grid(5, 5);
+        let config = SimulatorConfig::new();
+        let mut simulator = Simulator::new(&config, None, &network, &game);
+        simulator.run();
+
+        let avg_policy = simulator.report().avg_policy;
+        for i in 0..25 {
+            assert_abs_diff_eq!(avg_policy[(i, 1)], 0.95, epsilon = 0.01);
+        }
+    }
+
+    #[test]
+    fn test_dyn_game_same_as_const() {
+        let payoffs = [[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let game = MatrixGame::new(payoffs, InitialAction::Uniform);
+        let fixed = ActionCountingProcess::new(game.clone(), SoftmaxSample);
+        let dynamic = ActionCountingProcess::new(DynMatrixGame::from(game), SoftmaxSample);
+        let network = Network::grid(4, 4);
+        let mut config = SimulatorConfig::new();
+        config.set_window_steps(200);
+        config.set_max_windows(5);
+        for parallel in [false, true] {
+            config.set_parallel(parallel);
+            let mut simulator = Simulator::with_seed(&config, 3, &network, &fixed);
+            simulator.run();
+            let expected = simulator.report();
+            let mut simulator = Simulator::with_seed(&config, 3, &network, &dynamic);
+            simulator.run();
+            let report = simulator.report();
+            assert_eq!(report.steps, expected.steps);
+            assert_eq!(report.avg_policy, expected.avg_policy);
+            assert_eq!(report.configuration.process, expected.configuration.process);
+        }
+    }
+
+    #[test]
+    fn test_dyn_game_from_configuration() {
+        let game: DynMatrixGame = serde_json::from_value(serde_json::json!({
+            "payoff_matrix": [
+                [1.0, 0.0, 0.0, 0.0, 0.0],
+                [0.0, 2.0, 0.0, 0.0, 0.0],
+                [0.0, 0.0, 3.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0, 4.0, 0.0],
+                [0.0, 0.0, 0.0, 0.0, 5.0],
+            ],
+            "initial_action": {"Const": 4},
+        }))
+        .unwrap();
+        let process = ActionCountingProcess::new(game, BestResponseEpsilonError::new(0.0));
+        let network = Network::line(5);
+        let config = SimulatorConfig::new();
+        let mut simulator = Simulator::new(&config, None, &network, &process);
+        simulator.run();
+        let report = simulator.report();
+        assert_eq!(report.avg_policy.ncols(), 5);
+        assert_eq!(report.population_policy(), [0.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_player_state_serde() {
+        // States written before games had roles
+        let state: PlayerState<IntArray<2>> =
+            serde_json::from_str(r#"{"action_counts":[1,2]}"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&state).unwrap(),
+            r#"{"action_counts":[1,2]}"#
+        );
+        let bytes = bincode::serialize(&state).unwrap();
+        let state: PlayerState<IntArray<2>> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(state.action_counts.as_slice(), &[1, 2]);
+
+        let state = PlayerState {
+            action_counts: IntArray::from([3, 4]),
+            role: Role::Column,
+        };
+        let json = serde_json::to_value(&state).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"action_counts": [3, 4], "role": "Column"})
+        );
+        let bytes = bincode::serialize(&state).unwrap();
+        let state: PlayerState<IntArray<2>, Role> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(state.role, Role::Column);
+    }
+
     #[test]
     fn test_game_rock_paper_scissors() {
         let mut config = SimulatorConfig::new();
@@ -149,7 +305,7 @@ mod tests {
         config.set_max_windows(100);
 
         let payoffs = [[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
-        let game = ActionCountingProcess::<3, _>::new(
+        let game = ActionCountingProcess::<_, MatrixGame<3>>::new(
             MatrixGame::new(payoffs, InitialAction::Const(0)),
             BestResponseEpsilonError::new(0.1),
         );
@@ -160,7 +316,7 @@ mod tests {
         let avg_policy = report.avg_policy;
         //assert_eq!(avg_policy.len(), 3);
         assert_abs_diff_eq!(
-            (&avg_policy.column(0) + &avg_policy.column(1) + &avg_policy.column(2))
+            (&avg_policy.column(0) + &avg_policy.column(1) + avg_policy.column(2))
                 .mean()
                 .unwrap(),
             1.0,
@@ -171,4 +327,39 @@ mod tests {
         assert_abs_diff_eq!(a[1], 0.33, epsilon = 0.1);
         assert_abs_diff_eq!(a[2], 0.33, epsilon = 0.1);
     }
+
+    #[test]
+    fn test_rerun_from_configuration() {
+        let game = ActionCountingProcess::new(
+            MatrixGame::new([[1.0, 0.0], [0.0, 2.0]], InitialAction::Uniform),
+            BestResponseEpsilonError::new(0.2),
+        );
+        let network = Network::grid(4, 4);
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(100);
+        config.set_window_steps(100);
+        config.set_max_windows(3);
+        let mut simulator = Simulator::with_seed(&config, 77, &network, &game);
+        simulator.run();
+        let report = simulator.report();
+
+        let configuration = &report.configuration;
+        assert_eq!(configuration.process["game"], "br");
+        assert_eq!(
+            configuration.process["chooser"]["chooser"],
+            "best_response_eps"
+        );
+        assert_eq!(configuration.network["nodes"], 16);
+        assert!(configuration.network.get("attributes").is_none());
+        let game2: ActionCountingProcess<BestResponseEpsilonError, MatrixGame<2>> =
+            ActionCountingProcess::new(
+                serde_json::from_value(configuration.process["matrix_game"].clone()).unwrap(),
+                serde_json::from_value(configuration.process["chooser"].clone()).unwrap(),
+            );
+        let config2: SimulatorConfig =
+            serde_json::from_value(configuration.simulator.clone()).unwrap();
+        let mut simulator = Simulator::with_seed(&config2, report.seed, &network, &game2);
+        simulator.run();
+        assert_eq!(simulator.report().avg_policy, report.avg_policy);
+    }
 }
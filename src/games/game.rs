@@ -1,16 +1,97 @@
-use crate::process::fixarray::{FixArray, FloatArray};
+use crate::process::dynarray::{FloatDynArray, IntDynArray};
+use crate::process::fixarray::{CountArray, FixArray, FloatArray, IntArray, PayoffArray};
+use crate::process::network::Network;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
 
 pub type ActionId = usize;
 
-#[derive(Debug, Clone)]
+/// Game that nodes play with their neighbors, used by `ActionCountingProcess` and
+/// `RegretMatchingProcess`. Payoff matrices are indexed by a node's action and an opponent's action.
+pub trait Game: Debug + Clone + Send + Sync + Serialize {
+    /// Role of a node, `()` for symmetric games
+    type RoleT: Debug + Copy + Default + Send + Sync + Serialize + DeserializeOwned;
+    /// What a node's neighbors observe about it
+    type SignalT: Clone + Send + Sync;
+    /// Per-action payoffs, `FloatArray` for a number of actions known at compile time
+    /// and `FloatDynArray` otherwise
+    type PayoffsT: PayoffArray;
+    /// Per-action counts with the same size as `PayoffsT`
+    type CountsT: CountArray<FloatT = Self::PayoffsT>;
+
+    fn actions(&self) -> usize;
+
+    /// Fails if the game cannot be played on `network`, see `Process::validate`
+    fn validate(&self, _network: &Network) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn make_roles(&self, rng: &mut impl rand::Rng, network: &Network) -> Vec<Self::RoleT>;
+
+    fn make_initial_action(&self, rng: &mut impl rand::Rng) -> ActionId;
+
+    fn signal(&self, role: Self::RoleT, action: ActionId) -> Self::SignalT;
+
+    /// Action of the neighbor that sent `signal`, `None` if it is not an opponent of `role`
+    fn opponent_action(&self, role: Self::RoleT, signal: Self::SignalT) -> Option<ActionId>;
+
+    fn payoffs_sums(
+        &self,
+        role: Self::RoleT,
+        opponent_actions: impl Iterator<Item = ActionId>,
+    ) -> Self::PayoffsT;
+
+    fn expect_payoffs(
+        &self,
+        role: Self::RoleT,
+        opponent_strategy: Self::PayoffsT,
+    ) -> Self::PayoffsT;
+
+    /// Names of roles, empty for symmetric games
+    fn role_names(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Index of `role` in `role_names`
+    fn role_index(&self, _role: Self::RoleT) -> usize {
+        0
+    }
+
+    fn payoff_matrix(&self) -> Vec<Vec<f32>>;
+
+    /// Fails if `rows` is not an `actions` x `actions` matrix
+    fn set_payoff_matrix(&mut self, rows: &[Vec<f32>]) -> Result<(), String>;
+}
+
+/// Roles of symmetric games (`()`) are left out of serialized node states,
+/// so states saved before games had roles can still be read
+pub(crate) fn is_unit_role<RoleT>(_role: &RoleT) -> bool {
+    std::mem::size_of::<RoleT>() == 0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum InitialAction<const ACTIONS: usize> {
     Const(ActionId),
     Uniform,
     Distribution(FixArray<f32, ACTIONS>),
 }
 
-#[derive(Debug, Clone)]
+impl<const ACTIONS: usize> InitialAction<ACTIONS> {
+    /// Fails if a constant initial action is not one of `ACTIONS` actions
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            InitialAction::Const(action) if *action >= ACTIONS => {
+                Err(format!("initial action {} is out of range", action))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MatrixGame<const ACTIONS: usize> {
+    #[serde(with = "payoff_matrix_serde")]
     payoff_matrix: [[f32; ACTIONS]; ACTIONS],
     initial_action: InitialAction<ACTIONS>,
 }
@@ -21,16 +102,25 @@ impl<const ACTIONS: usize> MatrixGame<ACTIONS> {
         initial_action: InitialAction<ACTIONS>,
     ) -> Self {
         assert!(ACTIONS > 0);
-        assert!(match initial_action {
-            InitialAction::Const(a) => a <= ACTIONS,
-            InitialAction::Uniform | InitialAction::Distribution(_) => true,
-        });
+        if let Err(message) = initial_action.validate() {
+            panic!("{}", message);
+        }
         MatrixGame {
             payoff_matrix,
             initial_action,
         }
     }
 
+    pub fn payoff_matrix(&self) -> Vec<Vec<f32>> {
+        self.payoff_matrix.iter().map(|row| row.to_vec()).collect()
+    }
+
+    /// Fails if `rows` is not an `ACTIONS` x `ACTIONS` matrix
+    pub fn set_payoff_matrix(&mut self, rows: &[Vec<f32>]) -> Result<(), String> {
+        self.payoff_matrix = payoff_matrix_serde::from_rows(rows)?;
+        Ok(())
+    }
+
     pub fn make_initial_action(&self, rng: &mut impl rand::Rng) -> ActionId {
         match &self.initial_action {
             InitialAction::Const(action) => *action,
@@ -59,3 +149,360 @@ impl<const ACTIONS: usize> MatrixGame<ACTIONS> {
         )
     }
 }
+
+impl<const ACTIONS: usize> Game for MatrixGame<ACTIONS> {
+    type RoleT = ();
+    type SignalT = ActionId;
+    type PayoffsT = FloatArray<ACTIONS>;
+    type CountsT = IntArray<ACTIONS>;
+
+    fn actions(&self) -> usize {
+        ACTIONS
+    }
+
+    fn validate(&self, _network: &Network) -> Result<(), String> {
+        self.initial_action.validate()
+    }
+
+    fn make_roles(&self, _rng: &mut impl rand::Rng, network: &Network) -> Vec<()> {
+        vec![(); network.node_count()]
+    }
+
+    fn make_initial_action(&self, rng: &mut impl rand::Rng) -> ActionId {
+        MatrixGame::make_initial_action(self, rng)
+    }
+
+    fn signal(&self, _role: (), action: ActionId) -> ActionId {
+        action
+    }
+
+    fn opponent_action(&self, _role: (), signal: ActionId) -> Option<ActionId> {
+        Some(signal)
+    }
+
+    fn payoffs_sums(
+        &self,
+        _role: (),
+        opponent_actions: impl Iterator<Item = ActionId>,
+    ) -> FloatArray<ACTIONS> {
+        MatrixGame::payoffs_sums(self, opponent_actions)
+    }
+
+    fn expect_payoffs(
+        &self,
+        _role: (),
+        opponent_strategy: FloatArray<ACTIONS>,
+    ) -> FloatArray<ACTIONS> {
+        MatrixGame::expect_payoffs(self, opponent_strategy)
+    }
+
+    fn payoff_matrix(&self) -> Vec<Vec<f32>> {
+        MatrixGame::payoff_matrix(self)
+    }
+
+    fn set_payoff_matrix(&mut self, rows: &[Vec<f32>]) -> Result<(), String> {
+        MatrixGame::set_payoff_matrix(self, rows)
+    }
+}
+
+/// Runtime-sized counterpart of `InitialAction`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum DynInitialAction {
+    Const(ActionId),
+    Uniform,
+    Distribution(FloatDynArray),
+}
+
+impl<const ACTIONS: usize> From<InitialAction<ACTIONS>> for DynInitialAction {
+    fn from(initial_action: InitialAction<ACTIONS>) -> Self {
+        match initial_action {
+            InitialAction::Const(action) => DynInitialAction::Const(action),
+            InitialAction::Uniform => DynInitialAction::Uniform,
+            InitialAction::Distribution(a) => DynInitialAction::Distribution(a.into()),
+        }
+    }
+}
+
+/// Runtime-sized counterpart of `MatrixGame`, e.g. for games read from a configuration file.
+/// It has the same serialized form as `MatrixGame` and is played by the same processes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "DynMatrixGameParams")]
+pub struct DynMatrixGame {
+    payoff_matrix: Vec<FloatDynArray>,
+    initial_action: DynInitialAction,
+}
+
+#[derive(Deserialize)]
+struct DynMatrixGameParams {
+    payoff_matrix: Vec<Vec<f32>>,
+    initial_action: DynInitialAction,
+}
+
+impl TryFrom<DynMatrixGameParams> for DynMatrixGame {
+    type Error = String;
+
+    fn try_from(params: DynMatrixGameParams) -> Result<Self, String> {
+        let actions = params.payoff_matrix.len();
+        if actions == 0 || params.payoff_matrix.iter().any(|row| row.len() != actions) {
+            return Err("payoff matrix has to be square and non-empty".to_string());
+        }
+        match &params.initial_action {
+            DynInitialAction::Const(action) if *action >= actions => {
+                return Err(format!("initial action {} is out of range", action));
+            }
+            DynInitialAction::Distribution(a) if a.len() != actions => {
+                return Err(format!(
+                    "initial distribution has to have {} values",
+                    actions
+                ));
+            }
+            _ => {}
+        }
+        Ok(DynMatrixGame {
+            payoff_matrix: params
+                .payoff_matrix
+                .iter()
+                .map(|row| FloatDynArray::from_slice(row))
+                .collect(),
+            initial_action: params.initial_action,
+        })
+    }
+}
+
+impl DynMatrixGame {
+    pub fn new(payoff_matrix: Vec<Vec<f32>>, initial_action: DynInitialAction) -> Self {
+        DynMatrixGameParams {
+            payoff_matrix,
+            initial_action,
+        }
+        .try_into()
+        .unwrap()
+    }
+
+    pub fn actions(&self) -> usize {
+        self.payoff_matrix.len()
+    }
+
+    pub fn payoff_matrix(&self) -> Vec<Vec<f32>> {
+        self.payoff_matrix
+            .iter()
+            .map(|row| row.as_slice().to_vec())
+            .collect()
+    }
+
+    /// Fails if `rows` is not an `actions` x `actions` matrix
+    pub fn set_payoff_matrix(&mut self, rows: &[Vec<f32>]) -> Result<(), String> {
+        let actions = self.actions();
+        if rows.len() != actions || rows.iter().any(|row| row.len() != actions) {
+            return Err(format!("payoff matrix has to be {}x{}", actions, actions));
+        }
+        self.payoff_matrix = rows.iter().map(|r| FloatDynArray::from_slice(r)).collect();
+        Ok(())
+    }
+
+    pub fn make_initial_action(&self, rng: &mut impl rand::Rng) -> ActionId {
+        match &self.initial_action {
+            DynInitialAction::Const(action) => *action,
+            DynInitialAction::Uniform => rng.gen_range(0..self.actions()),
+            DynInitialAction::Distribution(a) => a.sample_index(rng),
+        }
+    }
+
+    pub fn payoffs_sums(&self, actions: impl Iterator<Item = ActionId>) -> FloatDynArray {
+        let mut payoffs = FloatDynArray::zeros(self.actions());
+        for opponent_action in actions {
+            for (i, row) in self.payoff_matrix.iter().enumerate() {
+                *payoffs.get_mut(i) += row.get(opponent_action);
+            }
+        }
+        payoffs
+    }
+
+    pub fn expect_payoffs(&self, opponent_strategy: FloatDynArray) -> FloatDynArray {
+        let payoffs: Vec<f32> = self
+            .payoff_matrix
+            .iter()
+            .map(|row| opponent_strategy.dot_product(row))
+            .collect();
+        FloatDynArray::from_slice(&payoffs)
+    }
+}
+
+impl Game for DynMatrixGame {
+    type RoleT = ();
+    type SignalT = ActionId;
+    type PayoffsT = FloatDynArray;
+    type CountsT = IntDynArray;
+
+    fn actions(&self) -> usize {
+        DynMatrixGame::actions(self)
+    }
+
+    fn make_roles(&self, _rng: &mut impl rand::Rng, network: &Network) -> Vec<()> {
+        vec![(); network.node_count()]
+    }
+
+    fn make_initial_action(&self, rng: &mut impl rand::Rng) -> ActionId {
+        DynMatrixGame::make_initial_action(self, rng)
+    }
+
+    fn signal(&self, _role: (), action: ActionId) -> ActionId {
+        action
+    }
+
+    fn opponent_action(&self, _role: (), signal: ActionId) -> Option<ActionId> {
+        Some(signal)
+    }
+
+    fn payoffs_sums(
+        &self,
+        _role: (),
+        opponent_actions: impl Iterator<Item = ActionId>,
+    ) -> FloatDynArray {
+        DynMatrixGame::payoffs_sums(self, opponent_actions)
+    }
+
+    fn expect_payoffs(&self, _role: (), opponent_strategy: FloatDynArray) -> FloatDynArray {
+        DynMatrixGame::expect_payoffs(self, opponent_strategy)
+    }
+
+    fn payoff_matrix(&self) -> Vec<Vec<f32>> {
+        DynMatrixGame::payoff_matrix(self)
+    }
+
+    fn set_payoff_matrix(&mut self, rows: &[Vec<f32>]) -> Result<(), String> {
+        DynMatrixGame::set_payoff_matrix(self, rows)
+    }
+}
+
+impl<const ACTIONS: usize> From<MatrixGame<ACTIONS>> for DynMatrixGame {
+    fn from(game: MatrixGame<ACTIONS>) -> Self {
+        DynMatrixGame::new(game.payoff_matrix(), game.initial_action.into())
+    }
+}
+
+/// Switches to the const-generic game when the number of actions is `ACTIONS`
+impl<const ACTIONS: usize> TryFrom<DynMatrixGame> for MatrixGame<ACTIONS> {
+    type Error = String;
+
+    fn try_from(game: DynMatrixGame) -> Result<Self, String> {
+        let payoff_matrix = payoff_matrix_serde::from_rows(&game.payoff_matrix())?;
+        let initial_action = match game.initial_action {
+            DynInitialAction::Const(action) => InitialAction::Const(action),
+            DynInitialAction::Uniform => InitialAction::Uniform,
+            DynInitialAction::Distribution(a) => InitialAction::Distribution(FixArray::from(
+                a.as_slice()
+                    .try_into()
+                    .map_err(|_| "invalid distribution")?,
+            )),
+        };
+        Ok(MatrixGame::new(payoff_matrix, initial_action))
+    }
+}
+
+pub(crate) mod payoff_matrix_serde {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer, const ACTIONS: usize>(
+        matrix: &[[f32; ACTIONS]; ACTIONS],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        matrix
+            .iter()
+            .map(|row| &row[..])
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const ACTIONS: usize>(
+        deserializer: D,
+    ) -> Result<[[f32; ACTIONS]; ACTIONS], D::Error> {
+        let rows = Vec::<Vec<f32>>::deserialize(deserializer)?;
+        from_rows(&rows).map_err(D::Error::custom)
+    }
+
+    pub fn from_rows<const ACTIONS: usize>(
+        rows: &[Vec<f32>],
+    ) -> Result<[[f32; ACTIONS]; ACTIONS], String> {
+        if rows.len() != ACTIONS || rows.iter().any(|row| row.len() != ACTIONS) {
+            return Err(format!("payoff matrix has to be {}x{}", ACTIONS, ACTIONS));
+        }
+        let mut matrix = [[0.0; ACTIONS]; ACTIONS];
+        for (target, row) in matrix.iter_mut().zip(rows) {
+            target.copy_from_slice(row);
+        }
+        Ok(matrix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::game::{DynInitialAction, DynMatrixGame, Game, InitialAction, MatrixGame};
+    use crate::process::fixarray::FixArray;
+    use crate::process::network::Network;
+
+    #[test]
+    fn test_dyn_matrix_game_conversion() {
+        let game = MatrixGame::new(
+            [[1.0, 2.0], [3.0, 4.0]],
+            InitialAction::Distribution(FixArray::from([0.25, 0.75])),
+        );
+        let dynamic = DynMatrixGame::from(game.clone());
+        assert_eq!(dynamic.actions(), 2);
+        assert_eq!(
+            serde_json::to_value(&dynamic).unwrap(),
+            serde_json::to_value(&game).unwrap()
+        );
+        let json = serde_json::to_string(&game).unwrap();
+        let parsed: DynMatrixGame = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.payoff_matrix(), game.payoff_matrix());
+
+        let fixed: MatrixGame<2> = dynamic.clone().try_into().unwrap();
+        assert_eq!(fixed.payoff_matrix(), game.payoff_matrix());
+        assert!(MatrixGame::<3>::try_from(dynamic).is_err());
+    }
+
+    #[test]
+    fn test_matrix_game_validation() {
+        let game: MatrixGame<2> = serde_json::from_value(serde_json::json!({
+            "payoff_matrix": [[1.0, 2.0], [3.0, 4.0]],
+            "initial_action": {"Const": 2},
+        }))
+        .unwrap();
+        assert_eq!(
+            Game::validate(&game, &Network::line(2)),
+            Err("initial action 2 is out of range".to_string())
+        );
+        let game = MatrixGame::new([[1.0, 2.0], [3.0, 4.0]], InitialAction::Const(1));
+        assert!(Game::validate(&game, &Network::line(2)).is_ok());
+    }
+
+    #[test]
+    fn test_dyn_matrix_game_validation() {
+        let parse = |value| serde_json::from_value::<DynMatrixGame>(value);
+        assert!(parse(serde_json::json!({
+            "payoff_matrix": [[1.0, 2.0], [3.0]],
+            "initial_action": "Uniform",
+        }))
+        .is_err());
+        assert!(parse(serde_json::json!({
+            "payoff_matrix": [[1.0, 2.0], [3.0, 4.0]],
+            "initial_action": {"Const": 2},
+        }))
+        .is_err());
+        let mut game = DynMatrixGame::new(
+            vec![vec![1.0, 2.0], vec![3.0, 4.0]],
+            DynInitialAction::Uniform,
+        );
+        assert_eq!(
+            game.payoffs_sums([0, 1, 1].into_iter()).as_slice(),
+            &[5.0, 11.0]
+        );
+        assert!(game.set_payoff_matrix(&[vec![1.0, 2.0, 3.0]]).is_err());
+        assert_eq!(game.payoff_matrix(), [[1.0, 2.0], [3.0, 4.0]]);
+        game.set_payoff_matrix(&[vec![0.0, 1.0], vec![1.0, 0.0]])
+            .unwrap();
+        assert_eq!(game.payoff_matrix(), [[0.0, 1.0], [1.0, 0.0]]);
+    }
+}
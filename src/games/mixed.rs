@@ -0,0 +1,319 @@
+use crate::games::game::ActionId;
+use crate::process::intervention::NodeSelection;
+use crate::process::network::Network;
+use crate::process::process::{NodeContext, Process};
+use crate::process::state::State;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MixedNodeState<FirstStateT, SecondStateT> {
+    First(FirstStateT),
+    Second(SecondStateT),
+}
+
+/// Heterogeneous population: nodes selected by `second_nodes` follow the second process,
+/// all other nodes the first one. More rules can be combined by nesting.
+#[derive(Debug, Clone)]
+pub struct MixedProcess<FirstT: Process, SecondT: Process> {
+    first: FirstT,
+    second: SecondT,
+    second_nodes: NodeSelection,
+}
+
+impl<FirstT: Process, SecondT: Process> MixedProcess<FirstT, SecondT> {
+    pub fn new(first: FirstT, second: SecondT, second_nodes: NodeSelection) -> Self {
+        assert_eq!(first.actions(), second.actions());
+        MixedProcess {
+            first,
+            second,
+            second_nodes,
+        }
+    }
+}
+
+/// Group names of a sub-process, its game name if it has no groups
+fn group_names<ProcessT: Process>(process: &ProcessT) -> Vec<String> {
+    let groups = process.groups();
+    if groups.is_empty() {
+        let name = match &process.configuration()["game"] {
+            Value::String(name) => name.clone(),
+            _ => "unknown".to_string(),
+        };
+        vec![name]
+    } else {
+        groups
+    }
+}
+
+impl<FirstT: Process, SecondT: Process<SignalT = FirstT::SignalT>> Process
+    for MixedProcess<FirstT, SecondT>
+{
+    type NodeStateT = MixedNodeState<FirstT::NodeStateT, SecondT::NodeStateT>;
+    type SignalT = FirstT::SignalT;
+
+    fn actions(&self) -> usize {
+        self.first.actions()
+    }
+
+    fn validate(&self, network: &Network) -> Result<(), String> {
+        self.first.validate(network)?;
+        self.second.validate(network)?;
+        self.second_nodes
+            .validate(network)
+            .map_err(|message| format!("second_nodes: {}", message))
+    }
+
+    fn make_initial_state(&self, rng: &mut impl Rng, network: &Network) -> State<Self> {
+        let second_nodes = self.second_nodes.resolve(network, rng);
+        let first = self.first.make_initial_state(rng, network);
+        let second = self.second.make_initial_state(rng, network);
+        let mut is_second = vec![false; network.node_count()];
+        for node in second_nodes {
+            is_second[node] = true;
+        }
+        let (node_states, actions) = is_second
+            .iter()
+            .enumerate()
+            .map(|(node, is_second)| {
+                if *is_second {
+                    (
+                        MixedNodeState::Second(second.node_states()[node].clone()),
+                        second.last_actions()[node],
+                    )
+                } else {
+                    (
+                        MixedNodeState::First(first.node_states()[node].clone()),
+                        first.last_actions()[node],
+                    )
+                }
+            })
+            .unzip();
+        State::new(node_states, actions)
+    }
+
+    fn signal(&self, node_state: &Self::NodeStateT, action: ActionId) -> Self::SignalT {
+        match node_state {
+            MixedNodeState::First(state) => self.first.signal(state, action),
+            MixedNodeState::Second(state) => self.second.signal(state, action),
+        }
+    }
+
+    fn node_step(
+        &self,
+        rng: &mut impl Rng,
+        context: &NodeContext,
+        node_state: &Self::NodeStateT,
+        last_action: ActionId,
+        neighbors: impl Iterator<Item = Self::SignalT>,
+    ) -> (Self::NodeStateT, ActionId) {
+        match node_state {
+            MixedNodeState::First(state) => {
+                let (state, action) =
+                    self.first
+                        .node_step(rng, context, state, last_action, neighbors);
+                (MixedNodeState::First(state), action)
+            }
+            MixedNodeState::Second(state) => {
+                let (state, action) =
+                    self.second
+                        .node_step(rng, context, state, last_action, neighbors);
+                (MixedNodeState::Second(state), action)
+            }
+        }
+    }
+
+    fn configuration(&self) -> Value {
+        json!({
+            "game": "mixed",
+            "first": self.first.configuration(),
+            "second": self.second.configuration(),
+            "second_nodes": self.second_nodes,
+        })
+    }
+
+    /// Group names of both sub-processes; names occurring more than once
+    /// get their occurrence number as a suffix, e.g. `br_1` and `br_2`
+    fn groups(&self) -> Vec<String> {
+        let mut groups = group_names(&self.first);
+        groups.extend(group_names(&self.second));
+        groups
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                if groups.iter().filter(|g| *g == name).count() > 1 {
+                    let occurrence = groups[..i].iter().filter(|g| *g == name).count() + 1;
+                    format!("{}_{}", name, occurrence)
+                } else {
+                    name.clone()
+                }
+            })
+            .collect()
+    }
+
+    fn node_group(&self, node_state: &Self::NodeStateT) -> usize {
+        match node_state {
+            MixedNodeState::First(state) => self.first.node_group(state),
+            MixedNodeState::Second(state) => {
+                group_names(&self.first).len() + self.second.node_group(state)
+            }
+        }
+    }
+
+    /// Payoff matrix of the first sub-process, `None` unless both sub-processes
+    /// have payoff matrices of the same size, so payoff interventions apply to both
+    fn payoff_matrix(&self) -> Option<Vec<Vec<f32>>> {
+        let first = self.first.payoff_matrix()?;
+        let second = self.second.payoff_matrix()?;
+        (first.len() == second.len()).then_some(first)
+    }
+
+    fn set_payoff_matrix(&mut self, payoff_matrix: &[Vec<f32>]) {
+        self.first.set_payoff_matrix(payoff_matrix);
+        self.second.set_payoff_matrix(payoff_matrix);
+    }
+
+    /// Adds `delta` to the payoff matrix of each sub-process, which may differ
+    fn add_payoffs(&mut self, delta: &[Vec<f32>]) {
+        self.first.add_payoffs(delta);
+        self.second.add_payoffs(delta);
+    }
+
+    fn node_state_changed(&self, previous: &Self::NodeStateT, next: &Self::NodeStateT) -> bool {
+        match (previous, next) {
+            (MixedNodeState::First(p), MixedNodeState::First(n)) => {
+                self.first.node_state_changed(p, n)
+            }
+            (MixedNodeState::Second(p), MixedNodeState::Second(n)) => {
+                self.second.node_state_changed(p, n)
+            }
+            _ => true,
+        }
+    }
+
+    fn perturb_node_state(
+        &self,
+        rng: &mut impl Rng,
+        node_state: &mut Self::NodeStateT,
+        strength: f32,
+    ) {
+        match node_state {
+            MixedNodeState::First(state) => self.first.perturb_node_state(rng, state, strength),
+            MixedNodeState::Second(state) => self.second.perturb_node_state(rng, state, strength),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::chooser::{BestResponseEpsilonError, DirectChooser};
+    use crate::games::counting::ActionCountingProcess;
+    use crate::games::game::{InitialAction, MatrixGame};
+    use crate::games::mixed::{MixedNodeState, MixedProcess};
+    use crate::games::regret::RegretMatchingProcess;
+    use crate::process::intervention::NodeSelection;
+    use crate::process::network::Network;
+    use crate::process::process::Process;
+    use crate::process::simulator::{Simulator, SimulatorConfig};
+    use serde_json::json;
+
+    #[test]
+    fn test_mixed_process_groups() {
+        let game = MatrixGame::new([[1.0, 0.5], [0.5, 0.0]], InitialAction::Const(1));
+        let process = MixedProcess::new(
+            ActionCountingProcess::new(game.clone(), BestResponseEpsilonError::new(0.0)),
+            RegretMatchingProcess::new(game, DirectChooser::new()),
+            NodeSelection::Attribute {
+                name: "rule".to_string(),
+                value: json!("rm"),
+            },
+        );
+        let mut network = Network::grid(4, 4);
+        network.set_node_attribute(
+            "rule",
+            (0..16)
+                .map(|node| json!(if node % 4 == 0 { "rm" } else { "br" }))
+                .collect(),
+        );
+        let mut config = SimulatorConfig::new();
+        config.set_report_details(true);
+        let mut simulator = Simulator::new(&config, None, &network, &process);
+        for (node, state) in simulator.state().node_states().iter().enumerate() {
+            match state {
+                MixedNodeState::First(_) => assert_ne!(node % 4, 0),
+                MixedNodeState::Second(_) => assert_eq!(node % 4, 0),
+            }
+        }
+        simulator.run();
+        let report = simulator.report();
+        let names: Vec<_> = report.groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, ["br", "rm"]);
+        assert_eq!(report.groups[0].nodes, 12);
+        assert_eq!(report.groups[1].nodes, 4);
+        assert_eq!(report.groups[0].policy, [1.0, 0.0]);
+        // Regret matching moved the nodes from their initial action to the dominant one
+        assert_eq!(report.groups[1].policy, [1.0, 0.0]);
+        let details = report.details.unwrap();
+        let regret_sums: Vec<_> = (0..16)
+            .step_by(4)
+            .map(|node| {
+                details.node_states[node]["Second"]["regret_sum"][0]
+                    .as_f64()
+                    .unwrap()
+            })
+            .collect();
+        assert!(regret_sums.iter().all(|r| *r > 0.0));
+        assert_eq!(report.configuration.process["game"], "mixed");
+        let attributes = &report.configuration.network["attributes"];
+        assert_eq!(attributes["rule"][0], "rm");
+        assert_eq!(attributes["rule"][1], "br");
+    }
+
+    #[test]
+    fn test_mixed_process_validation_and_names() {
+        let game = MatrixGame::new([[1.0, 0.5], [0.5, 0.0]], InitialAction::Const(1));
+        let process = MixedProcess::new(
+            ActionCountingProcess::new(game.clone(), BestResponseEpsilonError::new(0.0)),
+            ActionCountingProcess::new(game, BestResponseEpsilonError::new(0.1)),
+            NodeSelection::Attribute {
+                name: "rule".to_string(),
+                value: json!("eps"),
+            },
+        );
+        assert_eq!(process.groups(), ["br_1", "br_2"]);
+
+        let network = Network::grid(2, 2);
+        let config = SimulatorConfig::new();
+        let error = match Simulator::try_with_seed(&config, 0, &network, &process) {
+            Ok(_) => panic!("missing attribute was accepted"),
+            Err(error) => error.to_string(),
+        };
+        assert!(error.contains("network does not have attribute rule"));
+    }
+
+    #[test]
+    fn test_mixed_process_add_payoffs() {
+        let mut process = MixedProcess::new(
+            ActionCountingProcess::new(
+                MatrixGame::new([[1.0, 0.0], [0.0, 1.0]], InitialAction::Const(0)),
+                BestResponseEpsilonError::new(0.0),
+            ),
+            RegretMatchingProcess::new(
+                MatrixGame::new([[5.0, 5.0], [7.0, 7.0]], InitialAction::Const(0)),
+                DirectChooser::new(),
+            ),
+            NodeSelection::Nodes(vec![0]),
+        );
+        assert_eq!(process.payoff_matrix().unwrap(), [[1.0, 0.0], [0.0, 1.0]]);
+        process.add_payoffs(&[vec![0.5, 0.5], vec![0.5, 0.5]]);
+        assert_eq!(
+            process.first.payoff_matrix().unwrap(),
+            [[1.5, 0.5], [0.5, 1.5]]
+        );
+        assert_eq!(
+            process.second.payoff_matrix().unwrap(),
+            [[5.5, 5.5], [7.5, 7.5]]
+        );
+    }
+}
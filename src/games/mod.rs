@@ -1,4 +1,6 @@
+pub mod bimatrix;
 pub mod chooser;
 pub mod counting;
 pub mod game;
+pub mod mixed;
 pub mod regret;
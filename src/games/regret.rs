@@ -1,28 +1,28 @@
 use crate::games::chooser::ActionChooser;
-use crate::games::game::{ActionId, MatrixGame};
-use crate::process::fixarray::FixArray;
+use crate::games::game::{is_unit_role, ActionId, Game};
+use crate::process::fixarray::PayoffArray;
 use crate::process::network::Network;
-use crate::process::process::Process;
+use crate::process::process::{NodeContext, Process};
 use crate::process::state::State;
 use rand::Rng;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
-#[derive(Debug, Serialize)]
-pub struct PlayerState<const ACTIONS: usize> {
-    regret_sum: FixArray<f32, ACTIONS>,
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerState<RegretsT, RoleT = ()> {
+    regret_sum: RegretsT,
+    #[serde(default, skip_serializing_if = "is_unit_role")]
+    role: RoleT,
 }
 
-#[derive(Debug)]
-pub struct RegretMatchingProcess<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>> {
-    game: MatrixGame<ACTIONS>,
+#[derive(Debug, Clone)]
+pub struct RegretMatchingProcess<ActionChooserT: ActionChooser, GameT: Game> {
+    game: GameT,
     action_chooser: ActionChooserT,
 }
 
-impl<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>>
-    RegretMatchingProcess<ACTIONS, ActionChooserT>
-{
-    pub fn new(game: MatrixGame<ACTIONS>, action_chooser: ActionChooserT) -> Self {
+impl<ActionChooserT: ActionChooser, GameT: Game> RegretMatchingProcess<ActionChooserT, GameT> {
+    pub fn new(game: GameT, action_chooser: ActionChooserT) -> Self {
         RegretMatchingProcess {
             game,
             action_chooser,
@@ -30,31 +30,54 @@ impl<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>>
     }
 }
 
-impl<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>> Process
-    for RegretMatchingProcess<ACTIONS, ActionChooserT>
+impl<ActionChooserT: ActionChooser, GameT: Game> Process
+    for RegretMatchingProcess<ActionChooserT, GameT>
 {
-    type NodeStateT = PlayerState<ACTIONS>;
-    const ACTIONS: usize = ACTIONS;
+    type NodeStateT = PlayerState<GameT::PayoffsT, GameT::RoleT>;
+    type SignalT = GameT::SignalT;
+
+    fn actions(&self) -> usize {
+        self.game.actions()
+    }
+
+    fn validate(&self, network: &Network) -> Result<(), String> {
+        self.game.validate(network)
+    }
 
     fn make_initial_state(&self, rng: &mut impl Rng, network: &Network) -> State<Self> {
-        State::new_by(network, || {
-            (
-                PlayerState::<ACTIONS> {
-                    regret_sum: FixArray::default(),
-                },
-                self.game.make_initial_action(rng),
-            )
-        })
+        let roles = self.game.make_roles(rng, network);
+        let (node_states, actions) = roles
+            .into_iter()
+            .map(|role| {
+                (
+                    PlayerState {
+                        regret_sum: GameT::PayoffsT::zeros(self.game.actions()),
+                        role,
+                    },
+                    self.game.make_initial_action(rng),
+                )
+            })
+            .unzip();
+        State::new(node_states, actions)
+    }
+
+    fn signal(&self, node_state: &Self::NodeStateT, action: ActionId) -> GameT::SignalT {
+        self.game.signal(node_state.role, action)
     }
 
     fn node_step(
         &self,
         rng: &mut impl Rng,
-        node_state: &PlayerState<ACTIONS>,
+        _context: &NodeContext,
+        node_state: &Self::NodeStateT,
         last_action: ActionId,
-        neighbors: impl Iterator<Item = ActionId>,
-    ) -> (PlayerState<ACTIONS>, ActionId) {
-        let payoffs = self.game.payoffs_sums(neighbors);
+        neighbors: impl Iterator<Item = GameT::SignalT>,
+    ) -> (Self::NodeStateT, ActionId) {
+        let role = node_state.role;
+        let payoffs = self.game.payoffs_sums(
+            role,
+            neighbors.filter_map(|s| self.game.opponent_action(role, s)),
+        );
         let regret = payoffs.sub_scalar(payoffs.get(last_action));
         let regret_sum = node_state.regret_sum.add(&regret);
         let clamped = regret_sum.clamp_negatives();
@@ -63,22 +86,55 @@ impl<const ACTIONS: usize, ActionChooserT: ActionChooser<ACTIONS>> Process
             "Regret {} reg={} r_sum={} p_sum={}",
             node_state.action, regret, regret_sum, policy_sum
         );*/
-        (PlayerState { regret_sum }, action)
+        (PlayerState { regret_sum, role }, action)
     }
 
     fn configuration(&self) -> Value {
         json!({
             "game": "rm",
+            "matrix_game": self.game,
+            "chooser": self.action_chooser.configuration(),
         })
     }
+
+    fn groups(&self) -> Vec<String> {
+        self.game.role_names()
+    }
+
+    fn node_group(&self, node_state: &Self::NodeStateT) -> usize {
+        self.game.role_index(node_state.role)
+    }
+
+    fn payoff_matrix(&self) -> Option<Vec<Vec<f32>>> {
+        Some(self.game.payoff_matrix())
+    }
+
+    fn set_payoff_matrix(&mut self, payoff_matrix: &[Vec<f32>]) {
+        self.game
+            .set_payoff_matrix(payoff_matrix)
+            .expect("payoff matrix size is checked by SimulatorConfig::validate_for");
+    }
+
+    fn perturb_node_state(
+        &self,
+        _rng: &mut impl Rng,
+        node_state: &mut Self::NodeStateT,
+        strength: f32,
+    ) {
+        for action in 0..self.game.actions() {
+            *node_state.regret_sum.get_mut(action) *= 1.0 - strength.clamp(0.0, 1.0);
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::games::chooser::DirectChooser;
-    use crate::games::game::{InitialAction, MatrixGame};
+    use crate::games::game::{DynInitialAction, DynMatrixGame, InitialAction, MatrixGame};
     use crate::games::regret::RegretMatchingProcess;
+    use crate::process::convergence::Convergence;
     use crate::process::network::Network;
+    use crate::process::report::TerminationReason;
     use crate::process::simulator::{Simulator, SimulatorConfig};
     use approx::assert_abs_diff_eq;
     use ndarray::Axis;
@@ -105,9 +161,9 @@ mod tests {
 
         dbg!(&report);
         let avg_policy = &report.avg_policy;
-        assert_eq!(avg_policy.len(), 3);
+        assert_eq!(avg_policy.ncols(), 3);
         assert_abs_diff_eq!(
-            (&avg_policy.column(0) + &avg_policy.column(1) + &avg_policy.column(2))
+            (&avg_policy.column(0) + &avg_policy.column(1) + avg_policy.column(2))
                 .mean()
                 .unwrap(),
             1.0,
@@ -118,4 +174,154 @@ mod tests {
         assert_abs_diff_eq!(a[1], 0.33, epsilon = 0.1);
         assert_abs_diff_eq!(a[2], 0.33, epsilon = 0.1);
     }
+
+    #[test]
+    fn test_dyn_regret_matching_game_rock_paper_scissors() {
+        let mut config = SimulatorConfig::new();
+        config.set_termination_threshold(0.003);
+        config.set_window_steps(10000);
+        config.set_max_windows(100);
+
+        let payoffs = vec![
+            vec![0.0, -1.0, 1.0],
+            vec![1.0, 0.0, -1.0],
+            vec![-1.0, 1.0, 0.0],
+        ];
+        let game = RegretMatchingProcess::new(
+            DynMatrixGame::new(payoffs, DynInitialAction::Const(0)),
+            DirectChooser::new(),
+        );
+        let network = Network::line(2);
+        let mut simulator = Simulator::new(&config, None, &network, &game);
+        simulator.run();
+        let report = simulator.report();
+
+        let avg_policy = &report.avg_policy;
+        assert_eq!(avg_policy.ncols(), 3);
+        let a = avg_policy.mean_axis(Axis(0)).unwrap();
+        assert_abs_diff_eq!(a[0], 0.33, epsilon = 0.1);
+        assert_abs_diff_eq!(a[1], 0.33, epsilon = 0.1);
+        assert_abs_diff_eq!(a[2], 0.33, epsilon = 0.1);
+    }
+
+    #[test]
+    fn test_dyn_regret_matching_same_as_const() {
+        let payoffs = [[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let game = MatrixGame::new(payoffs, InitialAction::Uniform);
+        let fixed = RegretMatchingProcess::new(game.clone(), DirectChooser::new());
+        let dynamic = RegretMatchingProcess::new(DynMatrixGame::from(game), DirectChooser::new());
+        let network = Network::grid(3, 3);
+        let mut config = SimulatorConfig::new();
+        config.set_window_steps(300);
+        config.set_max_windows(5);
+        config.set_termination_threshold(0.0);
+        config.set_report_details(true);
+        let mut simulator = Simulator::with_seed(&config, 5, &network, &fixed);
+        simulator.run();
+        let expected = simulator.report().details.unwrap();
+        let mut simulator = Simulator::with_seed(&config, 5, &network, &dynamic);
+        simulator.run();
+        let details = simulator.report().details.unwrap();
+        assert_eq!(details.actions, expected.actions);
+        assert_eq!(details.node_states, expected.node_states);
+        assert_eq!(details.action_counts, expected.action_counts);
+    }
+
+    #[test]
+    fn test_checkpoint_resume() {
+        let payoffs = [[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let game = RegretMatchingProcess::new(
+            MatrixGame::new(payoffs, InitialAction::Uniform),
+            DirectChooser::new(),
+        );
+        let network = Network::grid(3, 3);
+        let checkpoint_path = std::env::temp_dir().join("pon-test-checkpoint.json");
+
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(500);
+        config.set_window_steps(300);
+        config.set_max_windows(5);
+        config.set_termination_threshold(0.0);
+        config.set_convergence(Convergence::MovingVariance { windows: 3 });
+        let expected = {
+            let mut simulator = Simulator::with_seed(&config, 77, &network, &game);
+            simulator.run();
+            simulator.report()
+        };
+
+        config.set_checkpoint(&checkpoint_path, 700);
+        let mut simulator = Simulator::with_seed(&config, 77, &network, &game);
+        simulator.run();
+
+        let mut resumed = Simulator::resume(&checkpoint_path, &config, &network, &game).unwrap();
+        assert_eq!(resumed.state().node_count(), 9);
+        resumed.run();
+        let report = resumed.report();
+        assert_eq!(report.seed, 77);
+        assert_eq!(report.steps, expected.steps);
+        assert_eq!(report.avg_policy, expected.avg_policy);
+        assert_eq!(report.checkpoint_error, None);
+
+        let mut max_change_config = config.clone();
+        max_change_config.set_convergence(Convergence::MaxChange);
+        let mut simulator = Simulator::with_seed(&max_change_config, 77, &network, &game);
+        simulator.run();
+        let error = Simulator::resume(&checkpoint_path, &config, &network, &game)
+            .err()
+            .unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        std::fs::remove_file(checkpoint_path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_checkpoint_does_not_stop_run() {
+        let game = RegretMatchingProcess::new(
+            MatrixGame::new([[1.0, 0.0], [0.0, 1.0]], InitialAction::Uniform),
+            DirectChooser::new(),
+        );
+        let network = Network::grid(3, 3);
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(100);
+        config.set_window_steps(100);
+        config.set_max_windows(3);
+        config.set_termination_threshold(0.0);
+        let missing_dir = std::env::temp_dir().join("pon-test-missing-dir");
+        config.set_checkpoint(&missing_dir.join("checkpoint.json"), 50);
+        let mut simulator = Simulator::with_seed(&config, 1, &network, &game);
+        assert_eq!(simulator.run(), TerminationReason::MaxWindows);
+        let report = simulator.report();
+        assert!(report
+            .checkpoint_error
+            .unwrap()
+            .contains("pon-test-missing-dir"));
+        assert!(!missing_dir.exists());
+    }
+
+    #[test]
+    fn test_report_details() {
+        let payoffs = [[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let game = RegretMatchingProcess::new(
+            MatrixGame::new(payoffs, InitialAction::Uniform),
+            DirectChooser::new(),
+        );
+        let network = Network::line(4);
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(100);
+        config.set_window_steps(100);
+        config.set_max_windows(3);
+        config.set_termination_threshold(0.0);
+        config.set_report_details(true);
+        let mut simulator = Simulator::new(&config, None, &network, &game);
+        simulator.run();
+
+        let report = simulator.report();
+        let details = report.details.unwrap();
+        assert_eq!(details.actions, simulator.state().last_actions());
+        assert_eq!(details.node_states.as_array().unwrap().len(), 4);
+        assert!(details.node_states[0]["regret_sum"].is_array());
+        assert_eq!(details.action_counts.dim(), (4, 3));
+        assert_eq!(details.action_counts.sum(), 4 * 300);
+        assert_eq!(details.node_deltas.len(), 4);
+        assert!(details.node_deltas.iter().all(|d| *d > 0.0 && *d < 1.0));
+    }
 }
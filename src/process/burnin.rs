@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Selects how long the simulation runs before measurement windows start
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub enum BurnIn {
+    /// Exactly `bootstrap_steps` steps
+    #[default]
+    Fixed,
+    /// Population action fractions are sampled every `sample_step` steps and burn-in ends
+    /// when the last `samples` samples pass the Geweke test, i.e. the mean of their first 10%
+    /// and of their last 50% do not differ by more than `z_threshold` standard errors
+    /// for any action. `bootstrap_steps` is then the maximal length of burn-in.
+    Geweke {
+        sample_step: usize,
+        samples: usize,
+        z_threshold: f32,
+    },
+}
+
+const GEWEKE_FIRST: f32 = 0.1;
+const GEWEKE_LAST: f32 = 0.5;
+
+fn mean_and_variance(values: impl Iterator<Item = f32> + Clone) -> (f32, f32, usize) {
+    let n = values.clone().count();
+    let mean = values.clone().sum::<f32>() / n as f32;
+    let variance = values.map(|v| (v - mean) * (v - mean)).sum::<f32>() / (n - 1).max(1) as f32;
+    (mean, variance, n)
+}
+
+/// Geweke z-score of a series; samples are assumed to be thinned enough to be
+/// approximately independent. Panics if `series` has less than 4 samples,
+/// `SimulatorConfig::validate` requires at least 20 for `BurnIn::Geweke`.
+pub fn geweke_z(series: &[f32]) -> f32 {
+    let first = ((series.len() as f32 * GEWEKE_FIRST).ceil() as usize).max(2);
+    let last = ((series.len() as f32 * GEWEKE_LAST).floor() as usize).max(2);
+    assert!(first + last <= series.len());
+    let (mean_a, var_a, n_a) = mean_and_variance(series[..first].iter().copied());
+    let (mean_b, var_b, n_b) = mean_and_variance(series[series.len() - last..].iter().copied());
+    let diff = mean_a - mean_b;
+    let error = (var_a / n_a as f32 + var_b / n_b as f32).sqrt();
+    if diff == 0.0 {
+        0.0
+    } else {
+        (diff / error).abs()
+    }
+}
+
+/// Sliding window of population action fractions used by `BurnIn::Geweke`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BurnInDetector {
+    samples: VecDeque<Vec<f32>>,
+}
+
+impl BurnInDetector {
+    /// Adds a sample and returns true if the window is full and stationary
+    pub fn push(&mut self, fractions: Vec<f32>, samples: usize, z_threshold: f32) -> bool {
+        if self.samples.len() == samples {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(fractions);
+        if self.samples.len() < samples {
+            return false;
+        }
+        let actions = self.samples[0].len();
+        (0..actions).all(|action| {
+            let series: Vec<f32> = self.samples.iter().map(|s| s[action]).collect();
+            geweke_z(&series) < z_threshold
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::chooser::BestResponseEpsilonError;
+    use crate::games::counting::ActionCountingProcess;
+    use crate::games::game::{InitialAction, MatrixGame};
+    use crate::process::burnin::{geweke_z, BurnIn, BurnInDetector};
+    use crate::process::network::Network;
+    use crate::process::simulator::{Simulator, SimulatorConfig};
+
+    #[test]
+    fn test_geweke() {
+        let constant = vec![0.5; 20];
+        assert_eq!(geweke_z(&constant), 0.0);
+        let trend: Vec<f32> = (0..20).map(|i| i as f32 / 20.0).collect();
+        assert!(geweke_z(&trend) > 5.0);
+        let noise: Vec<f32> = (0..20).map(|i| 0.5 + 0.1 * (i % 2) as f32).collect();
+        assert!(geweke_z(&noise) < 1.0);
+
+        let mut detector = BurnInDetector::default();
+        for i in 0..19 {
+            assert!(!detector.push(vec![i as f32 / 20.0, 1.0 - i as f32 / 20.0], 10, 2.0));
+        }
+        assert!(!detector.push(vec![0.95, 0.05], 10, 2.0));
+        for _ in 0..9 {
+            detector.push(vec![0.95, 0.05], 10, 2.0);
+        }
+        assert!(detector.push(vec![0.95, 0.05], 10, 2.0));
+    }
+
+    #[test]
+    fn test_adaptive_burn_in() {
+        let game = ActionCountingProcess::new(
+            MatrixGame::new([[1.0, 0.0], [0.0, 2.0]], InitialAction::Const(0)),
+            BestResponseEpsilonError::new(0.2),
+        );
+        let network = Network::grid(5, 5);
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(100_000);
+        config.set_window_steps(50);
+        config.set_max_windows(4);
+        config.set_burn_in(BurnIn::Geweke {
+            sample_step: 10,
+            samples: 30,
+            z_threshold: 2.0,
+        });
+        let run = |config: &SimulatorConfig| {
+            let mut simulator = Simulator::with_seed(config, 5, &network, &game);
+            simulator.run();
+            simulator.report()
+        };
+        let report = run(&config);
+        let burn_in_steps = report.burn_in_steps.unwrap();
+        assert!(burn_in_steps >= 290);
+        assert!(burn_in_steps < 100_000);
+        assert_eq!(burn_in_steps % 10, 0);
+        assert_eq!(report.steps, burn_in_steps + 200);
+
+        config.set_burn_in(BurnIn::Fixed);
+        config.set_bootstrap_steps(30);
+        assert_eq!(run(&config).burn_in_steps, Some(30));
+    }
+}
@@ -0,0 +1,344 @@
+use crate::process::utils::max_of_array;
+use ndarray::{Array1, Array2, Axis};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceCheck {
+    pub delta: f32,
+    pub converged: bool,
+}
+
+/// Decides when a run is converged. It is called at the end of every measurement window
+/// with the policies (nodes x actions) of the previous and of the just finished window.
+pub trait ConvergenceCriterion {
+    fn check(&mut self, previous: &Array2<f32>, current: &Array2<f32>) -> ConvergenceCheck;
+
+    /// Internal state stored in simulator checkpoints
+    fn save(&self) -> Value {
+        Value::Null
+    }
+
+    /// Fails if `value` was not saved by the same kind of criterion
+    fn load(&mut self, _value: Value) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+pub type ConvergenceFactory = Arc<dyn Fn(f32) -> Box<dyn ConvergenceCriterion> + Send + Sync>;
+
+/// Selects a convergence criterion in `SimulatorConfig`;
+/// all criteria compare their measure against `termination_threshold`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub enum Convergence {
+    /// Largest change of a per-node policy
+    #[default]
+    MaxChange,
+    /// Largest L1 distance between per-node policies
+    L1,
+    /// Largest L2 distance between per-node policies
+    L2,
+    /// Largest KL divergence of a per-node policy from its previous value
+    KL,
+    /// Largest change of the population mean policy
+    PopulationMean,
+    /// Largest variance of the population mean policy over the last `windows` windows
+    MovingVariance { windows: usize },
+    /// Inner criterion has to hold in `windows` consecutive windows
+    Consecutive {
+        windows: usize,
+        criterion: Box<Convergence>,
+    },
+    /// User defined criterion, the factory gets the termination threshold.
+    /// It is recorded only by name and cannot be deserialized.
+    #[serde(serialize_with = "serialize_custom", skip_deserializing)]
+    Custom(ConvergenceFactory),
+}
+
+fn serialize_custom<S: Serializer>(
+    _factory: &ConvergenceFactory,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str("custom")
+}
+
+impl Convergence {
+    pub fn build(&self, threshold: f32) -> Box<dyn ConvergenceCriterion> {
+        match self {
+            Convergence::MaxChange => Box::new(MaxChange { threshold }),
+            Convergence::L1 => Box::new(Divergence::new(DivergenceMetric::L1, threshold)),
+            Convergence::L2 => Box::new(Divergence::new(DivergenceMetric::L2, threshold)),
+            Convergence::KL => Box::new(Divergence::new(DivergenceMetric::KL, threshold)),
+            Convergence::PopulationMean => Box::new(PopulationMean { threshold }),
+            Convergence::MovingVariance { windows } => {
+                Box::new(MovingVariance::new(*windows, threshold))
+            }
+            Convergence::Consecutive { windows, criterion } => {
+                Box::new(Consecutive::new(*windows, criterion.build(threshold)))
+            }
+            Convergence::Custom(factory) => factory(threshold),
+        }
+    }
+}
+
+fn population_mean(policies: &Array2<f32>) -> Array1<f32> {
+    policies
+        .mean_axis(Axis(0))
+        .unwrap_or_else(|| Array1::zeros(policies.ncols()))
+}
+
+pub struct MaxChange {
+    threshold: f32,
+}
+
+impl ConvergenceCriterion for MaxChange {
+    fn check(&mut self, previous: &Array2<f32>, current: &Array2<f32>) -> ConvergenceCheck {
+        let delta = max_of_array(current - previous);
+        ConvergenceCheck {
+            delta,
+            converged: delta < self.threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DivergenceMetric {
+    L1,
+    L2,
+    KL,
+}
+
+const KL_EPSILON: f32 = 1e-6;
+
+impl DivergenceMetric {
+    pub fn distance<'a>(
+        &self,
+        previous: impl Iterator<Item = &'a f32>,
+        current: impl Iterator<Item = &'a f32>,
+    ) -> f32 {
+        let pairs = previous.zip(current);
+        match self {
+            DivergenceMetric::L1 => pairs.map(|(p, c)| (c - p).abs()).sum(),
+            DivergenceMetric::L2 => pairs.map(|(p, c)| (c - p) * (c - p)).sum::<f32>().sqrt(),
+            DivergenceMetric::KL => pairs
+                .map(|(p, c)| {
+                    let (p, c) = (p + KL_EPSILON, c + KL_EPSILON);
+                    c * (c / p).ln()
+                })
+                .sum(),
+        }
+    }
+}
+
+pub struct Divergence {
+    metric: DivergenceMetric,
+    threshold: f32,
+}
+
+impl Divergence {
+    pub fn new(metric: DivergenceMetric, threshold: f32) -> Self {
+        Divergence { metric, threshold }
+    }
+}
+
+impl ConvergenceCriterion for Divergence {
+    fn check(&mut self, previous: &Array2<f32>, current: &Array2<f32>) -> ConvergenceCheck {
+        let delta = previous
+            .rows()
+            .into_iter()
+            .zip(current.rows())
+            .map(|(p, c)| self.metric.distance(p.iter(), c.iter()))
+            .fold(0.0f32, f32::max);
+        ConvergenceCheck {
+            delta,
+            converged: delta < self.threshold,
+        }
+    }
+}
+
+pub struct PopulationMean {
+    threshold: f32,
+}
+
+impl ConvergenceCriterion for PopulationMean {
+    fn check(&mut self, previous: &Array2<f32>, current: &Array2<f32>) -> ConvergenceCheck {
+        let delta = (population_mean(current) - population_mean(previous))
+            .iter()
+            .fold(0.0f32, |m, v| m.max(v.abs()));
+        ConvergenceCheck {
+            delta,
+            converged: delta < self.threshold,
+        }
+    }
+}
+
+pub struct MovingVariance {
+    windows: usize,
+    threshold: f32,
+    means: VecDeque<Array1<f32>>,
+}
+
+impl MovingVariance {
+    pub fn new(windows: usize, threshold: f32) -> Self {
+        assert!(windows > 1);
+        MovingVariance {
+            windows,
+            threshold,
+            means: VecDeque::with_capacity(windows),
+        }
+    }
+}
+
+impl ConvergenceCriterion for MovingVariance {
+    fn check(&mut self, _previous: &Array2<f32>, current: &Array2<f32>) -> ConvergenceCheck {
+        if self.means.len() == self.windows {
+            self.means.pop_front();
+        }
+        self.means.push_back(population_mean(current));
+        if self.means.len() < self.windows {
+            return ConvergenceCheck {
+                delta: f32::INFINITY,
+                converged: false,
+            };
+        }
+        let n = self.means.len() as f32;
+        let mean = self
+            .means
+            .iter()
+            .fold(Array1::<f32>::zeros(current.ncols()), |a, m| a + m)
+            / n;
+        let variance = self
+            .means
+            .iter()
+            .fold(Array1::<f32>::zeros(current.ncols()), |a, m| {
+                a + (m - &mean).mapv(|x| x * x)
+            })
+            / n;
+        let delta = variance.iter().fold(0.0f32, |m, v| m.max(*v));
+        ConvergenceCheck {
+            delta,
+            converged: delta < self.threshold,
+        }
+    }
+
+    fn save(&self) -> Value {
+        json!(self.means)
+    }
+
+    fn load(&mut self, value: Value) -> Result<(), String> {
+        self.means = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+pub struct Consecutive {
+    windows: usize,
+    criterion: Box<dyn ConvergenceCriterion>,
+    hits: usize,
+}
+
+impl Consecutive {
+    pub fn new(windows: usize, criterion: Box<dyn ConvergenceCriterion>) -> Self {
+        Consecutive {
+            windows,
+            criterion,
+            hits: 0,
+        }
+    }
+}
+
+impl ConvergenceCriterion for Consecutive {
+    fn check(&mut self, previous: &Array2<f32>, current: &Array2<f32>) -> ConvergenceCheck {
+        let check = self.criterion.check(previous, current);
+        if check.converged {
+            self.hits += 1;
+        } else {
+            self.hits = 0;
+        }
+        ConvergenceCheck {
+            delta: check.delta,
+            converged: self.hits >= self.windows,
+        }
+    }
+
+    fn save(&self) -> Value {
+        json!({"hits": self.hits, "criterion": self.criterion.save()})
+    }
+
+    fn load(&mut self, mut value: Value) -> Result<(), String> {
+        self.hits = serde_json::from_value(value["hits"].take()).map_err(|e| e.to_string())?;
+        self.criterion.load(value["criterion"].take())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::process::convergence::Convergence;
+    use approx::assert_abs_diff_eq;
+    use ndarray::{arr2, Array2};
+
+    fn delta(convergence: Convergence, previous: &Array2<f32>, current: &Array2<f32>) -> f32 {
+        convergence.build(0.1).check(previous, current).delta
+    }
+
+    #[test]
+    fn test_divergences() {
+        let previous = arr2(&[[0.5, 0.5], [1.0, 0.0]]);
+        let current = arr2(&[[0.6, 0.4], [0.5, 0.5]]);
+        assert_abs_diff_eq!(delta(Convergence::MaxChange, &previous, &current), 0.5);
+        assert_abs_diff_eq!(delta(Convergence::L1, &previous, &current), 1.0);
+        assert_abs_diff_eq!(
+            delta(Convergence::L2, &previous, &current),
+            0.5f32.sqrt(),
+            epsilon = 0.0001
+        );
+        assert!(delta(Convergence::KL, &previous, &current) > 1.0);
+        assert_abs_diff_eq!(
+            delta(Convergence::PopulationMean, &previous, &current),
+            0.2,
+            epsilon = 0.0001
+        );
+        assert_abs_diff_eq!(delta(Convergence::KL, &current, &current), 0.0);
+    }
+
+    #[test]
+    fn test_population_mean_ignores_node_swaps() {
+        let previous = arr2(&[[1.0, 0.0], [0.0, 1.0]]);
+        let current = arr2(&[[0.0, 1.0], [1.0, 0.0]]);
+        let mut criterion = Convergence::PopulationMean.build(0.01);
+        assert!(criterion.check(&previous, &current).converged);
+        let mut criterion = Convergence::L1.build(0.01);
+        assert!(!criterion.check(&previous, &current).converged);
+    }
+
+    #[test]
+    fn test_moving_variance() {
+        let a = arr2(&[[0.5, 0.5]]);
+        let b = arr2(&[[0.52, 0.48]]);
+        let mut criterion = Convergence::MovingVariance { windows: 3 }.build(0.001);
+        assert!(!criterion.check(&a, &a).converged);
+        assert!(!criterion.check(&a, &b).converged);
+        let check = criterion.check(&b, &a);
+        assert!(check.converged);
+        assert_abs_diff_eq!(check.delta, 0.0000888, epsilon = 0.000001);
+        let c = arr2(&[[0.9, 0.1]]);
+        assert!(!criterion.check(&a, &c).converged);
+    }
+
+    #[test]
+    fn test_consecutive() {
+        let a = arr2(&[[0.5, 0.5]]);
+        let b = arr2(&[[0.9, 0.1]]);
+        let mut criterion = Convergence::Consecutive {
+            windows: 2,
+            criterion: Box::new(Convergence::MaxChange),
+        }
+        .build(0.01);
+        assert!(!criterion.check(&a, &a).converged);
+        assert!(!criterion.check(&a, &b).converged);
+        assert!(!criterion.check(&b, &b).converged);
+        assert!(criterion.check(&b, &b).converged);
+    }
+}
@@ -0,0 +1,282 @@
+use crate::games::game::InitialAction;
+use crate::process::fixarray::FixArray;
+use crate::process::network::Network;
+use crate::process::process::Process;
+use crate::process::report::{RunReport, TerminationReason};
+use crate::process::simulator::{Simulator, SimulatorConfig};
+use crate::process::utils::derive_seed;
+use serde::Serialize;
+
+/// Potential scale reduction factor of equally long chains of a scalar.
+/// Values close to 1 mean that within-chain and between-chain variances agree.
+pub fn r_hat(chains: &[Vec<f32>]) -> f32 {
+    let m = chains.len() as f32;
+    let n = chains[0].len() as f32;
+    assert!(chains.len() > 1 && n > 1.0);
+    assert!(chains.iter().all(|c| c.len() == chains[0].len()));
+    let means: Vec<f32> = chains.iter().map(|c| c.iter().sum::<f32>() / n).collect();
+    let mean = means.iter().sum::<f32>() / m;
+    let between = n / (m - 1.0) * means.iter().map(|x| (x - mean) * (x - mean)).sum::<f32>();
+    let within = chains
+        .iter()
+        .zip(&means)
+        .map(|(c, cm)| c.iter().map(|x| (x - cm) * (x - cm)).sum::<f32>() / (n - 1.0))
+        .sum::<f32>()
+        / m;
+    if within == 0.0 {
+        return if between == 0.0 { 1.0 } else { f32::INFINITY };
+    }
+    let variance = (n - 1.0) / n * within + between / n;
+    (variance / within).sqrt()
+}
+
+/// Initial distributions for `chains` chains, each concentrated (with probability `weight`)
+/// on a different action, cycling through actions.
+pub fn dispersed_initial_actions<const ACTIONS: usize>(
+    chains: usize,
+    weight: f32,
+) -> Vec<InitialAction<ACTIONS>> {
+    assert!(ACTIONS > 1 && (0.0..=1.0).contains(&weight));
+    let rest = (1.0 - weight) / (ACTIONS - 1) as f32;
+    (0..chains)
+        .map(|chain| {
+            let mut distribution = [rest; ACTIONS];
+            distribution[chain % ACTIONS] = weight;
+            InitialAction::Distribution(FixArray::from(distribution))
+        })
+        .collect()
+}
+
+#[derive(Serialize, Debug)]
+pub struct GelmanRubinReport {
+    /// R-hat of population action fractions, one value per action
+    pub r_hat: Vec<f32>,
+    /// Step of the last R-hat evaluation
+    pub step: usize,
+    /// True if chains were stopped by the R-hat rule
+    pub stopped: bool,
+    pub reports: Vec<RunReport>,
+}
+
+impl GelmanRubinReport {
+    pub fn max_r_hat(&self) -> f32 {
+        self.r_hat.iter().fold(0.0f32, |a, b| a.max(*b))
+    }
+}
+
+/// Runs chains (one per process, typically differing only in the initial distribution,
+/// see `dispersed_initial_actions`) in lockstep and samples population action fractions
+/// every `sample_step` steps. R-hat is computed from the second half of samples while
+/// all chains are running; chains that outlive the others are then finished independently.
+/// Traces and checkpoints are disabled, as chains would overwrite each other's files.
+pub struct GelmanRubin<'a, ProcessT: Process> {
+    config: SimulatorConfig,
+    network: &'a Network,
+    processes: &'a [ProcessT],
+    seed: u64,
+    sample_step: usize,
+    min_samples: usize,
+    threshold: Option<f32>,
+}
+
+impl<'a, ProcessT: Process> GelmanRubin<'a, ProcessT> {
+    pub fn new(
+        config: &SimulatorConfig,
+        network: &'a Network,
+        processes: &'a [ProcessT],
+        seed: u64,
+    ) -> Self {
+        assert!(processes.len() > 1);
+        let mut config = config.clone();
+        config.disable_outputs();
+        GelmanRubin {
+            config,
+            network,
+            processes,
+            seed,
+            sample_step: 100,
+            min_samples: 20,
+            threshold: None,
+        }
+    }
+
+    pub fn set_sample_step(&mut self, sample_step: usize) {
+        assert!(sample_step > 0);
+        self.sample_step = sample_step;
+    }
+    /// Minimal number of samples per chain before the stopping rule is applied
+    pub fn set_min_samples(&mut self, min_samples: usize) {
+        assert!(min_samples >= 4);
+        self.min_samples = min_samples;
+    }
+    /// All chains are terminated with `TerminationReason::RHat` when R-hat
+    /// of every action is below `threshold` (e.g. 1.01)
+    pub fn set_stopping_threshold(&mut self, threshold: f32) {
+        self.threshold = Some(threshold);
+    }
+
+    pub fn run(&self) -> GelmanRubinReport {
+        let mut simulators: Vec<_> = self
+            .processes
+            .iter()
+            .enumerate()
+            .map(|(i, process)| {
+                Simulator::with_seed(
+                    &self.config,
+                    derive_seed(self.seed, i),
+                    self.network,
+                    process,
+                )
+            })
+            .collect();
+        let mut samples: Vec<Vec<Vec<f32>>> = simulators
+            .iter()
+            .map(|s| vec![s.action_fractions()])
+            .collect();
+        let mut r_hat = vec![f32::INFINITY; self.processes[0].actions()];
+        let mut step = 0;
+        let mut stopped = false;
+        loop {
+            step += self.sample_step;
+            let mut all_running = true;
+            for (simulator, chain) in simulators.iter_mut().zip(&mut samples) {
+                all_running &= simulator.run_until(step).is_none();
+                chain.push(simulator.action_fractions());
+            }
+            if !all_running {
+                break;
+            }
+            if samples[0].len() >= 4 {
+                r_hat = compute_r_hat(&samples);
+            }
+            if self.threshold.is_some_and(|threshold| {
+                samples[0].len() >= self.min_samples && r_hat.iter().all(|r| *r < threshold)
+            }) {
+                for simulator in &mut simulators {
+                    simulator.finish(TerminationReason::RHat);
+                }
+                stopped = true;
+                break;
+            }
+        }
+        let reports = simulators
+            .iter_mut()
+            .map(|simulator| {
+                simulator.run();
+                simulator.report()
+            })
+            .collect();
+        GelmanRubinReport {
+            r_hat,
+            step: if stopped {
+                step
+            } else {
+                step - self.sample_step
+            },
+            stopped,
+            reports,
+        }
+    }
+}
+
+fn compute_r_hat(samples: &[Vec<Vec<f32>>]) -> Vec<f32> {
+    let n = samples[0].len();
+    (0..samples[0][0].len())
+        .map(|action| {
+            let chains: Vec<Vec<f32>> = samples
+                .iter()
+                .map(|chain| chain[n / 2..].iter().map(|s| s[action]).collect())
+                .collect();
+            r_hat(&chains)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::chooser::{BestResponseEpsilonError, SoftmaxSample};
+    use crate::games::counting::ActionCountingProcess;
+    use crate::games::game::MatrixGame;
+    use crate::process::diagnostics::{dispersed_initial_actions, r_hat, GelmanRubin};
+    use crate::process::network::Network;
+    use crate::process::report::TerminationReason;
+    use crate::process::testing::windows_config;
+    use approx::assert_abs_diff_eq;
+
+    #[test]
+    fn test_r_hat() {
+        let a = vec![0.1, 0.3, 0.2, 0.4];
+        let b = vec![0.3, 0.1, 0.4, 0.2];
+        assert!(r_hat(&[a.clone(), b.clone()]) < 1.0);
+        let c = vec![0.9, 0.8, 0.9, 0.8];
+        assert!(r_hat(&[a.clone(), c]) > 2.0);
+        assert_abs_diff_eq!(r_hat(&[vec![0.5; 4], vec![0.5; 4]]), 1.0);
+        assert_eq!(r_hat(&[vec![0.5; 4], vec![0.6; 4]]), f32::INFINITY);
+    }
+
+    #[test]
+    fn test_chains_do_not_write_outputs() {
+        let processes: Vec<_> = dispersed_initial_actions::<2>(2, 0.9)
+            .into_iter()
+            .map(|initial_action| {
+                ActionCountingProcess::new(
+                    MatrixGame::new([[1.0, 0.0], [0.0, 1.0]], initial_action),
+                    SoftmaxSample,
+                )
+            })
+            .collect();
+        let network = Network::grid(3, 3);
+        let trace_path = std::env::temp_dir().join("pon-test-gelman-rubin-trace.json");
+        let checkpoint_path = std::env::temp_dir().join("pon-test-gelman-rubin-checkpoint.json");
+        let mut config = windows_config(100, 2);
+        config.set_trace_path(&trace_path);
+        config.set_checkpoint(&checkpoint_path, 50);
+        let report = GelmanRubin::new(&config, &network, &processes, 3).run();
+        assert_eq!(report.reports.len(), 2);
+        assert!(!trace_path.exists());
+        assert!(!checkpoint_path.exists());
+    }
+
+    #[test]
+    fn test_gelman_rubin() {
+        let chains = |payoff_matrix| -> Vec<_> {
+            dispersed_initial_actions::<2>(4, 0.9)
+                .into_iter()
+                .map(|initial| {
+                    ActionCountingProcess::new(
+                        MatrixGame::new(payoff_matrix, initial),
+                        BestResponseEpsilonError::new(0.2),
+                    )
+                })
+                .collect()
+        };
+        let network = Network::grid(5, 5);
+        let config = windows_config(100, 20);
+
+        // Accumulated counts lock chains into their initial conventions
+        let processes = chains([[1.0, 0.0], [0.0, 1.0]]);
+        let mut diagnostic = GelmanRubin::new(&config, &network, &processes, 3);
+        diagnostic.set_sample_step(10);
+        diagnostic.set_stopping_threshold(1.1);
+        let report = diagnostic.run();
+        assert!(!report.stopped);
+        assert_eq!(report.step, 2090);
+        assert!(report.max_r_hat() > 1.5);
+        for r in &report.reports {
+            assert_eq!(r.termination, Some(TerminationReason::MaxWindows));
+        }
+
+        let processes = chains([[0.0, 0.0], [0.0, 0.0]]);
+        let mut diagnostic = GelmanRubin::new(&config, &network, &processes, 3);
+        diagnostic.set_sample_step(10);
+        diagnostic.set_stopping_threshold(1.1);
+        let report = diagnostic.run();
+        assert!(report.stopped);
+        assert!(report.step < 2090);
+        assert!(report.max_r_hat() < 1.1);
+        for r in &report.reports {
+            assert_eq!(r.termination, Some(TerminationReason::RHat));
+            assert_eq!(r.steps, report.step);
+        }
+    }
+}
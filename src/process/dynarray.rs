@@ -0,0 +1,281 @@
+use crate::process::fixarray::{CountArray, FixArray, PayoffArray, WeightArray};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::fmt::{Debug, Display, Formatter};
+
+/// Runtime-sized counterpart of `FixArray`, small sizes are stored inline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DynArray<T>(SmallVec<[T; 8]>);
+
+pub type FloatDynArray = DynArray<f32>;
+pub type IntDynArray = DynArray<u32>;
+
+impl<T: Default + Copy> DynArray<T> {
+    pub fn zeros(size: usize) -> Self {
+        DynArray(SmallVec::from_elem(Default::default(), size))
+    }
+
+    #[inline]
+    pub fn from_slice(values: &[T]) -> Self {
+        DynArray(SmallVec::from_slice(values))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    #[inline]
+    pub fn get(&self, index: usize) -> T {
+        self.0[index]
+    }
+
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> &mut T {
+        &mut self.0[index]
+    }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    fn map(&self, f: impl Fn(T) -> T) -> Self {
+        DynArray(self.0.iter().map(|x| f(*x)).collect())
+    }
+}
+
+impl<T: Default + Copy, const SIZE: usize> From<FixArray<T, SIZE>> for DynArray<T> {
+    fn from(array: FixArray<T, SIZE>) -> Self {
+        DynArray::from_slice(array.as_slice())
+    }
+}
+
+impl DynArray<u32> {
+    pub fn as_float(&self) -> FloatDynArray {
+        DynArray(self.0.iter().map(|v| *v as f32).collect())
+    }
+}
+
+impl DynArray<f32> {
+    #[inline]
+    pub fn sub_scalar(&self, value: f32) -> FloatDynArray {
+        self.map(|x| x - value)
+    }
+
+    #[inline]
+    pub fn add_scalar(&self, value: f32) -> FloatDynArray {
+        self.map(|x| x + value)
+    }
+
+    #[inline]
+    pub fn add(&self, other: &Self) -> Self {
+        debug_assert_eq!(self.len(), other.len());
+        let mut result = self.clone();
+        result
+            .0
+            .iter_mut()
+            .zip(other.0.iter())
+            .for_each(|(x, y)| *x += y);
+        result
+    }
+
+    #[inline]
+    pub fn sum(&self) -> f32 {
+        self.0.iter().sum()
+    }
+
+    pub fn exp(&self) -> FloatDynArray {
+        self.map(|x| x.exp())
+    }
+
+    pub fn normalize(&self) -> Self {
+        debug_assert!(self.is_finite());
+        let sum = self.sum();
+        if sum <= 0.0 {
+            return self.clone();
+        }
+        self.map(|x| x / sum)
+    }
+
+    pub fn normalize_to_policy(&self) -> Self {
+        debug_assert!(self.is_finite());
+        let sum = self.sum();
+        if sum <= 0.0 {
+            return DynArray(SmallVec::from_elem(1.0f32 / self.len() as f32, self.len()));
+        }
+        self.map(|x| x / sum)
+    }
+
+    pub fn clamp_negatives(&self) -> Self {
+        self.map(|x| 0.0f32.max(x))
+    }
+
+    pub fn argmax(&self) -> usize {
+        let (max_idx, _) =
+            self.0
+                .iter()
+                .enumerate()
+                .fold((0, self.0[0]), |(idx_max, val_max), (idx, val)| {
+                    if &val_max > val {
+                        (idx_max, val_max)
+                    } else {
+                        (idx, *val)
+                    }
+                });
+        max_idx
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.0.iter().all(|v| v.is_finite())
+    }
+
+    pub fn dot_product(&self, other: &FloatDynArray) -> f32 {
+        debug_assert_eq!(self.len(), other.len());
+        let mut result = 0.0;
+        for (x, y) in self.0.iter().zip(other.0.iter()) {
+            result += x * y;
+        }
+        result
+    }
+
+    pub fn sample_index(&self, rng: &mut impl Rng) -> usize {
+        debug_assert!(self.is_finite());
+        let size = self.len();
+        let sum = self.sum();
+        if sum <= 0.0 {
+            return rng.gen_range(0..size);
+        }
+        let mut value: f32 = rng.gen_range(0.0..sum);
+        for i in 0..size - 1 {
+            if value < self.0[i] {
+                return i;
+            }
+            value -= self.0[i];
+        }
+        size - 1
+    }
+}
+
+impl WeightArray for FloatDynArray {
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn sample_index(&self, rng: &mut impl Rng) -> usize {
+        DynArray::sample_index(self, rng)
+    }
+
+    fn argmax(&self) -> usize {
+        DynArray::argmax(self)
+    }
+
+    fn exp(&self) -> Self {
+        DynArray::exp(self)
+    }
+}
+
+impl PayoffArray for FloatDynArray {
+    fn zeros(size: usize) -> Self {
+        DynArray::zeros(size)
+    }
+
+    fn get(&self, index: usize) -> f32 {
+        DynArray::get(self, index)
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut f32 {
+        DynArray::get_mut(self, index)
+    }
+
+    fn sub_scalar(&self, value: f32) -> Self {
+        DynArray::sub_scalar(self, value)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        DynArray::add(self, other)
+    }
+
+    fn normalize(&self) -> Self {
+        DynArray::normalize(self)
+    }
+
+    fn clamp_negatives(&self) -> Self {
+        DynArray::clamp_negatives(self)
+    }
+}
+
+impl CountArray for IntDynArray {
+    type FloatT = FloatDynArray;
+
+    fn zeros(size: usize) -> Self {
+        DynArray::zeros(size)
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut u32 {
+        DynArray::get_mut(self, index)
+    }
+
+    fn as_float(&self) -> FloatDynArray {
+        DynArray::as_float(self)
+    }
+}
+
+impl<T: Display + Debug> Display for DynArray<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.0.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::process::dynarray::DynArray;
+    use crate::process::fixarray::FixArray;
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_same_as_fixarray() {
+        let values = [0.45f32, -0.2, 0.1, 2.5];
+        let fixed = FixArray::from(values);
+        let dynamic = DynArray::from(fixed.clone());
+        assert_eq!(dynamic.as_slice(), &values);
+        assert_eq!(dynamic.argmax(), fixed.argmax());
+        assert_eq!(
+            dynamic.clamp_negatives().normalize().as_slice(),
+            fixed.clamp_negatives().normalize().as_slice()
+        );
+        assert_eq!(dynamic.dot_product(&dynamic), fixed.dot_product(&fixed));
+
+        let weights = dynamic.clamp_negatives();
+        let mut rng1 = SmallRng::seed_from_u64(7);
+        let mut rng2 = SmallRng::seed_from_u64(7);
+        for _ in 0..100 {
+            assert_eq!(
+                weights.sample_index(&mut rng1),
+                fixed.clamp_negatives().sample_index(&mut rng2)
+            );
+        }
+        assert_eq!(
+            DynArray::<f32>::zeros(3).normalize_to_policy().as_slice(),
+            &[1.0 / 3.0; 3]
+        );
+    }
+
+    #[test]
+    fn test_serde() {
+        let f = DynArray::from_slice(&[1.5f32, -2.0, 0.25]);
+        let json = serde_json::to_string(&f).unwrap();
+        assert_eq!(json, "[1.5,-2.0,0.25]");
+        assert_eq!(serde_json::from_str::<DynArray<f32>>(&json).unwrap(), f);
+        let fixed: FixArray<f32, 3> = serde_json::from_str(&json).unwrap();
+        assert_eq!(DynArray::from(fixed), f);
+    }
+}
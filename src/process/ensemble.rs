@@ -0,0 +1,223 @@
+use crate::process::network::Network;
+use crate::process::process::Process;
+use crate::process::report::RunReport;
+use crate::process::simulator::{Simulator, SimulatorConfig};
+use crate::process::utils::derive_seed;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::Serialize;
+
+/// Mean, standard deviation and percentile bootstrap confidence interval of the mean,
+/// all NaN if there are no values
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Statistic {
+    pub mean: f32,
+    pub std: f32,
+    pub ci_low: f32,
+    pub ci_high: f32,
+}
+
+impl Statistic {
+    pub fn new(
+        values: &[f32],
+        rng: &mut impl Rng,
+        bootstrap_samples: usize,
+        confidence: f32,
+    ) -> Self {
+        let n = values.len();
+        if n == 0 {
+            return Statistic {
+                mean: f32::NAN,
+                std: f32::NAN,
+                ci_low: f32::NAN,
+                ci_high: f32::NAN,
+            };
+        }
+        let mean = values.iter().sum::<f32>() / n as f32;
+        let std = if n > 1 {
+            (values.iter().map(|v| (v - mean) * (v - mean)).sum::<f32>() / (n - 1) as f32).sqrt()
+        } else {
+            0.0
+        };
+        let mut means: Vec<f32> = (0..bootstrap_samples)
+            .map(|_| (0..n).map(|_| values[rng.gen_range(0..n)]).sum::<f32>() / n as f32)
+            .collect();
+        means.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let quantile = |q: f32| {
+            means
+                .get(((means.len() as f32 * q) as usize).min(means.len().saturating_sub(1)))
+                .copied()
+                .unwrap_or(mean)
+        };
+        let alpha = (1.0 - confidence) / 2.0;
+        Statistic {
+            mean,
+            std,
+            ci_low: quantile(alpha),
+            ci_high: quantile(1.0 - alpha),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct EnsembleReport {
+    pub seed: u64,
+    /// Population mean policy, one statistic per action.
+    /// Replicas in which all nodes are zealots are left out.
+    pub policy: Vec<Statistic>,
+    pub steps: Statistic,
+    /// Fraction of replicas that converged or reached an absorbing state
+    pub converged: f32,
+    /// Reports of individual replicas, ordered by replica index
+    pub reports: Vec<RunReport>,
+}
+
+/// Runs independent replicas of one configuration in parallel.
+/// Replica `i` uses seed `derive_seed(seed, i)`, so an ensemble is reproducible from its seed.
+/// Traces and checkpoints are disabled, as replicas would overwrite each other's files.
+pub struct Ensemble<'a, ProcessT: Process> {
+    config: SimulatorConfig,
+    network: &'a Network,
+    process: &'a ProcessT,
+    replicas: usize,
+    seed: u64,
+    bootstrap_samples: usize,
+    confidence: f32,
+}
+
+impl<'a, ProcessT: Process> Ensemble<'a, ProcessT> {
+    pub fn new(
+        config: &SimulatorConfig,
+        network: &'a Network,
+        process: &'a ProcessT,
+        replicas: usize,
+        seed: u64,
+    ) -> Self {
+        assert!(replicas > 0);
+        let mut config = config.clone();
+        config.disable_outputs();
+        Ensemble {
+            config,
+            network,
+            process,
+            replicas,
+            seed,
+            bootstrap_samples: 1000,
+            confidence: 0.95,
+        }
+    }
+
+    pub fn set_bootstrap_samples(&mut self, bootstrap_samples: usize) {
+        self.bootstrap_samples = bootstrap_samples;
+    }
+    pub fn set_confidence(&mut self, confidence: f32) {
+        assert!(confidence > 0.0 && confidence < 1.0);
+        self.confidence = confidence;
+    }
+
+    pub fn run(&self) -> EnsembleReport {
+        let reports: Vec<RunReport> = (0..self.replicas)
+            .into_par_iter()
+            .map(|replica| {
+                let seed = derive_seed(self.seed, replica);
+                let mut simulator =
+                    Simulator::with_seed(&self.config, seed, self.network, self.process);
+                simulator.run();
+                simulator.report()
+            })
+            .collect();
+
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(self.seed);
+        let mut statistic = |values: &[f32]| {
+            Statistic::new(values, &mut rng, self.bootstrap_samples, self.confidence)
+        };
+        let policies: Vec<_> = reports
+            .iter()
+            .map(|r| r.population_policy())
+            .filter(|p| !p.is_empty())
+            .collect();
+        let policy = (0..self.process.actions())
+            .map(|action| statistic(&policies.iter().map(|p| p[action]).collect::<Vec<_>>()))
+            .collect();
+        let steps = statistic(&reports.iter().map(|r| r.steps as f32).collect::<Vec<_>>());
+        let converged =
+            reports.iter().filter(|r| r.converged()).count() as f32 / reports.len() as f32;
+        EnsembleReport {
+            seed: self.seed,
+            policy,
+            steps,
+            converged,
+            reports,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::process::ensemble::{Ensemble, Statistic};
+    use crate::process::intervention::NodeSelection;
+    use crate::process::network::Network;
+    use crate::process::testing::{coordination_process, windows_config};
+    use crate::process::utils::derive_seed;
+    use approx::assert_abs_diff_eq;
+    use rand::SeedableRng;
+    use rand_xoshiro::Xoshiro256PlusPlus;
+
+    #[test]
+    fn test_statistic() {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(1);
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let statistic = Statistic::new(&values, &mut rng, 2000, 0.9);
+        assert_abs_diff_eq!(statistic.mean, 3.0);
+        assert_abs_diff_eq!(statistic.std, 2.5f32.sqrt());
+        assert!(statistic.ci_low < 3.0 && statistic.ci_low > 1.5);
+        assert!(statistic.ci_high > 3.0 && statistic.ci_high < 4.5);
+
+        let statistic = Statistic::new(&[2.0], &mut rng, 100, 0.95);
+        assert_eq!(
+            (statistic.std, statistic.ci_low, statistic.ci_high),
+            (0.0, 2.0, 2.0)
+        );
+        assert!(Statistic::new(&[], &mut rng, 100, 0.95).mean.is_nan());
+    }
+
+    #[test]
+    fn test_all_zealots() {
+        let process = coordination_process();
+        let network = Network::grid(3, 3);
+        let mut config = windows_config(100, 2);
+        config.add_zealots(NodeSelection::RandomFraction(1.0), 0);
+        let report = Ensemble::new(&config, &network, &process, 3, 1).run();
+        assert_eq!(report.reports.len(), 3);
+        assert_eq!(report.policy.len(), 2);
+        assert!(report.policy.iter().all(|p| p.mean.is_nan()));
+        assert_eq!(report.steps.mean, report.reports[0].steps as f32);
+    }
+
+    #[test]
+    fn test_ensemble() {
+        let game = coordination_process();
+        let network = Network::grid(3, 3);
+        let config = windows_config(50, 3);
+
+        let ensemble = Ensemble::new(&config, &network, &game, 6, 42);
+        let report = ensemble.run();
+        assert_eq!(report.reports.len(), 6);
+        for (i, r) in report.reports.iter().enumerate() {
+            assert_eq!(r.seed, derive_seed(42, i));
+        }
+        assert_eq!(report.policy.len(), 2);
+        assert_abs_diff_eq!(
+            report.policy[0].mean + report.policy[1].mean,
+            1.0,
+            epsilon = 0.0001
+        );
+        assert!(report.policy[0].ci_low <= report.policy[0].mean);
+        assert!(report.policy[0].ci_high >= report.policy[0].mean);
+        assert_eq!(report.steps.mean, 200.0);
+
+        let again = ensemble.run();
+        assert_eq!(again.policy, report.policy);
+    }
+}
@@ -1,5 +1,6 @@
 use rand::Rng;
-use serde::{Serialize, Serializer};
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt::{Debug, Display, Formatter};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -8,6 +9,52 @@ pub struct FixArray<T, const SIZE: usize>([T; SIZE]);
 pub type FloatArray<const SIZE: usize> = FixArray<f32, SIZE>;
 pub type IntArray<const SIZE: usize> = FixArray<u32, SIZE>;
 
+/// Per-action weights that an `ActionChooser` selects from,
+/// implemented by both `FloatArray` and the runtime-sized `FloatDynArray`
+pub trait WeightArray {
+    fn size(&self) -> usize;
+
+    fn sample_index(&self, rng: &mut impl Rng) -> usize;
+
+    fn argmax(&self) -> usize;
+
+    fn exp(&self) -> Self;
+}
+
+/// Per-action payoffs or regrets kept by game processes,
+/// implemented by both `FloatArray` and the runtime-sized `FloatDynArray`
+pub trait PayoffArray:
+    WeightArray + Debug + Clone + Send + Sync + Serialize + DeserializeOwned
+{
+    /// Zeros for `size` actions, `size` has to be `SIZE` for `FloatArray`
+    fn zeros(size: usize) -> Self;
+
+    fn get(&self, index: usize) -> f32;
+
+    fn get_mut(&mut self, index: usize) -> &mut f32;
+
+    fn sub_scalar(&self, value: f32) -> Self;
+
+    fn add(&self, other: &Self) -> Self;
+
+    fn normalize(&self) -> Self;
+
+    fn clamp_negatives(&self) -> Self;
+}
+
+/// Per-action counts kept by game processes,
+/// implemented by both `IntArray` and the runtime-sized `IntDynArray`
+pub trait CountArray: Debug + Clone + Send + Sync + Serialize + DeserializeOwned {
+    type FloatT: PayoffArray;
+
+    /// Zeros for `size` actions, `size` has to be `SIZE` for `IntArray`
+    fn zeros(size: usize) -> Self;
+
+    fn get_mut(&mut self, index: usize) -> &mut u32;
+
+    fn as_float(&self) -> Self::FloatT;
+}
+
 impl<T: Default + Copy, const SIZE: usize> Default for FixArray<T, SIZE> {
     fn default() -> Self {
         FixArray([Default::default(); SIZE])
@@ -29,6 +76,11 @@ impl<T: Default + Copy, const SIZE: usize> FixArray<T, SIZE> {
     pub fn get_mut(&mut self, index: usize) -> &mut T {
         &mut self.0[index]
     }
+
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
 }
 
 impl<const SIZE: usize> FixArray<u32, SIZE> {
@@ -37,6 +89,7 @@ impl<const SIZE: usize> FixArray<u32, SIZE> {
     }
 }
 
+#[allow(clippy::needless_range_loop)]
 impl<const SIZE: usize> FixArray<f32, SIZE> {
     #[inline]
     pub fn sub_scalar(&self, value: f32) -> FixArray<f32, SIZE> {
@@ -148,6 +201,72 @@ impl<const SIZE: usize> FixArray<f32, SIZE> {
     }
 }
 
+impl<const SIZE: usize> WeightArray for FloatArray<SIZE> {
+    fn size(&self) -> usize {
+        SIZE
+    }
+
+    fn sample_index(&self, rng: &mut impl Rng) -> usize {
+        FixArray::sample_index(self, rng)
+    }
+
+    fn argmax(&self) -> usize {
+        FixArray::argmax(self)
+    }
+
+    fn exp(&self) -> Self {
+        FixArray::exp(self)
+    }
+}
+
+impl<const SIZE: usize> PayoffArray for FloatArray<SIZE> {
+    fn zeros(size: usize) -> Self {
+        debug_assert_eq!(size, SIZE);
+        FixArray::default()
+    }
+
+    fn get(&self, index: usize) -> f32 {
+        FixArray::get(self, index)
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut f32 {
+        FixArray::get_mut(self, index)
+    }
+
+    fn sub_scalar(&self, value: f32) -> Self {
+        FixArray::sub_scalar(self, value)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        FixArray::add(self, other)
+    }
+
+    fn normalize(&self) -> Self {
+        FixArray::normalize(self)
+    }
+
+    fn clamp_negatives(&self) -> Self {
+        FixArray::clamp_negatives(self)
+    }
+}
+
+impl<const SIZE: usize> CountArray for IntArray<SIZE> {
+    type FloatT = FloatArray<SIZE>;
+
+    fn zeros(size: usize) -> Self {
+        debug_assert_eq!(size, SIZE);
+        FixArray::default()
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut u32 {
+        FixArray::get_mut(self, index)
+    }
+
+    fn as_float(&self) -> FloatArray<SIZE> {
+        FixArray::as_float(self)
+    }
+}
+
 impl<T: Display + Debug, const SIZE: usize> Display for FixArray<T, SIZE> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self.0)
@@ -163,6 +282,20 @@ impl<T: Serialize, const SIZE: usize> Serialize for FixArray<T, SIZE> {
     }
 }
 
+impl<'de, T: Deserialize<'de>, const SIZE: usize> Deserialize<'de> for FixArray<T, SIZE> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let values = Vec::<T>::deserialize(deserializer)?;
+        let len = values.len();
+        values
+            .try_into()
+            .map(FixArray)
+            .map_err(|_| D::Error::invalid_length(len, &format!("{} values", SIZE).as_str()))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::process::fixarray::FixArray;
@@ -201,6 +334,15 @@ mod tests {
         sample_check([0.45, 0.45, 0.1]);
     }
 
+    #[test]
+    fn test_serde() {
+        let f = FixArray::from([1.5f32, -2.0, 0.25]);
+        let json = serde_json::to_string(&f).unwrap();
+        assert_eq!(json, "[1.5,-2.0,0.25]");
+        assert_eq!(serde_json::from_str::<FixArray<f32, 3>>(&json).unwrap(), f);
+        assert!(serde_json::from_str::<FixArray<f32, 2>>(&json).is_err());
+    }
+
     #[test]
     fn test_clamp() {
         assert_eq!(
@@ -0,0 +1,294 @@
+use crate::games::game::ActionId;
+use crate::process::network::Network;
+use rand::seq::index::sample;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Selects a set of nodes of a network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NodeSelection {
+    Nodes(Vec<usize>),
+    /// `count` nodes with the highest degree (ties broken by node index)
+    TopDegree(usize),
+    /// Random subset with the given fraction of nodes
+    RandomFraction(f32),
+    /// Nodes whose attribute (see `Network::set_node_attribute`) has the given value
+    Attribute {
+        name: String,
+        value: serde_json::Value,
+    },
+    /// One side of a bipartite network: nodes at an odd distance from the lowest node
+    /// of their component
+    Bipartite,
+}
+
+impl NodeSelection {
+    /// Fails if the selection cannot be resolved in `network`
+    pub fn validate(&self, network: &Network) -> Result<(), String> {
+        let node_count = network.node_count();
+        match self {
+            NodeSelection::Nodes(nodes) => match nodes.iter().find(|n| **n >= node_count) {
+                Some(node) => Err(format!(
+                    "node {} is not in the network of {} nodes",
+                    node, node_count
+                )),
+                None => Ok(()),
+            },
+            NodeSelection::Attribute { name, .. } if network.node_attribute(name).is_none() => {
+                Err(format!("network does not have attribute {}", name))
+            }
+            NodeSelection::Bipartite => bipartite_side(network).map(|_| ()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Panics if `validate` fails
+    pub fn resolve(&self, network: &Network, rng: &mut impl Rng) -> Vec<usize> {
+        let node_count = network.node_count();
+        match self {
+            NodeSelection::Nodes(nodes) => {
+                assert!(nodes.iter().all(|n| *n < node_count));
+                nodes.clone()
+            }
+            NodeSelection::TopDegree(count) => {
+                let graph = network.graph();
+                let mut nodes: Vec<usize> = (0..node_count).collect();
+                nodes.sort_by_key(|n| {
+                    std::cmp::Reverse(graph.neighbors((*n as u32).into()).count())
+                });
+                nodes.truncate(*count);
+                nodes
+            }
+            NodeSelection::RandomFraction(fraction) => {
+                let amount = (fraction.clamp(0.0, 1.0) * node_count as f32).round() as usize;
+                let mut nodes = sample(rng, node_count, amount).into_vec();
+                nodes.sort_unstable();
+                nodes
+            }
+            NodeSelection::Attribute { name, value } => network
+                .node_attribute(name)
+                .unwrap_or_else(|| panic!("Network does not have attribute {}", name))
+                .iter()
+                .enumerate()
+                .filter(|(_, v)| *v == value)
+                .map(|(node, _)| node)
+                .collect(),
+            NodeSelection::Bipartite => {
+                bipartite_side(network).unwrap_or_else(|message| panic!("{}", message))
+            }
+        }
+    }
+}
+
+fn bipartite_side(network: &Network) -> Result<Vec<usize>, String> {
+    let graph = network.graph();
+    let mut side: Vec<Option<bool>> = vec![None; network.node_count()];
+    let mut queue = VecDeque::new();
+    for start in 0..network.node_count() {
+        if side[start].is_some() {
+            continue;
+        }
+        side[start] = Some(false);
+        queue.push_back(start);
+        while let Some(node) = queue.pop_front() {
+            let node_side = side[node].unwrap();
+            for other in graph.neighbors((node as u32).into()) {
+                match side[other.index()] {
+                    None => {
+                        side[other.index()] = Some(!node_side);
+                        queue.push_back(other.index());
+                    }
+                    Some(other_side) if other_side == node_side => {
+                        return Err("network is not bipartite".to_string());
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+    }
+    Ok(side
+        .iter()
+        .enumerate()
+        .filter(|(_, side)| **side == Some(true))
+        .map(|(node, _)| node)
+        .collect())
+}
+
+/// Committed nodes that always play `action`, see `SimulatorConfig::add_zealots`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Zealots {
+    pub nodes: NodeSelection,
+    pub action: ActionId,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum InterventionKind {
+    /// Replaces the payoff matrix of the process (`Process::payoff_matrix`).
+    /// Games with roles have one such matrix, for `BimatrixGame` it holds the payoffs
+    /// of the row role and column payoffs are never changed.
+    SetPayoffs(Vec<Vec<f32>>),
+    /// Adds a matrix to the payoff matrix of the process (see `SetPayoffs`),
+    /// e.g. a subsidy of an action
+    AddPayoffs(Vec<Vec<f32>>),
+    /// Sets the last action of selected nodes, zealots are skipped
+    ForceActions {
+        nodes: NodeSelection,
+        action: ActionId,
+    },
+    /// Perturbs node states of selected nodes, see `Process::perturb_node_state`
+    PerturbStates { nodes: NodeSelection, strength: f32 },
+}
+
+/// Change applied to a running simulation after `step` steps, see `SimulatorConfig::add_intervention`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Intervention {
+    pub step: usize,
+    pub kind: InterventionKind,
+}
+
+impl Intervention {
+    pub fn new(step: usize, kind: InterventionKind) -> Self {
+        Intervention { step, kind }
+    }
+
+    /// Payoff changes are part of the process, not of the simulator state,
+    /// so they have to be applied again when a run is resumed.
+    pub fn changes_payoffs(&self) -> bool {
+        matches!(
+            self.kind,
+            InterventionKind::SetPayoffs(_) | InterventionKind::AddPayoffs(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::chooser::BestResponseEpsilonError;
+    use crate::games::counting::{ActionCountingProcess, PlayerState};
+    use crate::games::game::{InitialAction, MatrixGame};
+    use crate::process::fixarray::IntArray;
+    use crate::process::intervention::{Intervention, InterventionKind, NodeSelection};
+    use crate::process::network::Network;
+    use crate::process::simulator::{Simulator, SimulatorConfig};
+    use crate::process::testing::windows_config;
+    use crate::process::trace::{TraceReader, TraceRecord};
+    use rand::rngs::SmallRng;
+    use rand::SeedableRng;
+    use serde_json::json;
+
+    #[test]
+    fn test_node_selection() {
+        let network = Network::line(5);
+        let mut rng = SmallRng::seed_from_u64(0);
+        assert_eq!(
+            NodeSelection::Nodes(vec![4, 1]).resolve(&network, &mut rng),
+            vec![4, 1]
+        );
+        assert_eq!(
+            NodeSelection::TopDegree(2).resolve(&network, &mut rng),
+            vec![1, 2]
+        );
+        let nodes = NodeSelection::RandomFraction(0.4).resolve(&network, &mut rng);
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes[0] < nodes[1] && nodes[1] < 5);
+
+        let mut network = network;
+        network.set_node_attribute(
+            "group",
+            vec![json!("a"), json!("b"), json!("a"), json!(1), json!("b")],
+        );
+        let selection = NodeSelection::Attribute {
+            name: "group".to_string(),
+            value: json!("b"),
+        };
+        assert_eq!(selection.resolve(&network, &mut rng), vec![1, 4]);
+
+        assert_eq!(
+            NodeSelection::Bipartite.resolve(&network, &mut rng),
+            vec![1, 3]
+        );
+        assert_eq!(
+            NodeSelection::Bipartite.resolve(&Network::grid(3, 3), &mut rng),
+            vec![1, 3, 5, 7]
+        );
+        let triangle = Network::random(&mut rng, 3, 1.0);
+        assert_eq!(
+            NodeSelection::Bipartite.validate(&triangle),
+            Err("network is not bipartite".to_string())
+        );
+    }
+
+    #[test]
+    fn test_interventions() {
+        let game = ActionCountingProcess::new(
+            MatrixGame::new([[1.0, 0.5], [0.5, 0.0]], InitialAction::Const(0)),
+            BestResponseEpsilonError::new(0.0),
+        );
+        let network = Network::line(4);
+        let trace_path = std::env::temp_dir().join("pon-test-interventions.trace");
+        let checkpoint_path = std::env::temp_dir().join("pon-test-interventions.json");
+        let mut config = windows_config(5, 2);
+        config.set_report_details(true);
+        // Action 1 becomes dominant
+        config.add_intervention(Intervention::new(
+            3,
+            InterventionKind::AddPayoffs(vec![vec![0.0, 0.0], vec![1.0, 1.0]]),
+        ));
+        config.add_intervention(Intervention::new(
+            3,
+            InterventionKind::PerturbStates {
+                nodes: NodeSelection::RandomFraction(1.0),
+                strength: 1.0,
+            },
+        ));
+        config.add_intervention(Intervention::new(
+            12,
+            InterventionKind::ForceActions {
+                nodes: NodeSelection::TopDegree(1),
+                action: 0,
+            },
+        ));
+        config.set_trace_path(&trace_path);
+
+        let run = |config: &SimulatorConfig| {
+            let mut simulator = Simulator::with_seed(config, 1, &network, &game);
+            let mut actions = Vec::new();
+            while simulator.run_until(simulator.current_step() + 1).is_none() {
+                actions.push(simulator.state().last_actions().to_vec());
+            }
+            (actions, simulator.report())
+        };
+        let (actions, report) = run(&config);
+        assert_eq!(actions[2], vec![0, 0, 0, 0]);
+        assert_eq!(actions[3], vec![1, 1, 1, 1]);
+        // Forced after step 12, node 1 returns to the dominant action in the next step
+        assert_eq!(actions[12], vec![1, 1, 1, 1]);
+        assert_eq!(report.details.unwrap().actions, vec![1, 1, 1, 1]);
+        assert_eq!(
+            report.configuration.process["matrix_game"]["payoff_matrix"],
+            serde_json::json!([[1.0, 0.5], [0.5, 0.0]])
+        );
+
+        let interventions: Vec<_> = TraceReader::<PlayerState<IntArray<2>>>::open(&trace_path)
+            .unwrap()
+            .filter_map(|r| match r.unwrap() {
+                TraceRecord::Intervention(frame) => Some((frame.step, frame.nodes)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            interventions,
+            vec![(3, vec![]), (3, vec![0, 1, 2, 3]), (12, vec![1])]
+        );
+
+        // Payoff changes are applied again when a run is resumed
+        config.set_checkpoint(&checkpoint_path, 6);
+        let (_, expected) = run(&config);
+        let mut resumed = Simulator::resume(&checkpoint_path, &config, &network, &game).unwrap();
+        resumed.run();
+        assert_eq!(resumed.report().avg_policy, expected.avg_policy);
+        std::fs::remove_file(trace_path).unwrap();
+        std::fs::remove_file(checkpoint_path).unwrap();
+    }
+}
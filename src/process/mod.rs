@@ -1,9 +1,21 @@
+pub mod burnin;
+pub mod convergence;
+pub mod diagnostics;
+pub mod dynarray;
+pub mod ensemble;
 pub mod fixarray;
+pub mod intervention;
 mod map;
 pub mod monitor;
 pub mod network;
+pub mod observer;
+#[allow(clippy::module_inception)]
 pub mod process;
+pub mod replay;
 pub mod report;
 pub mod simulator;
 pub mod state;
+#[cfg(test)]
+pub(crate) mod testing;
+pub mod trace;
 pub mod utils;
@@ -3,14 +3,17 @@ use rand::distributions::{Alphanumeric, Bernoulli};
 use rand::Rng;
 use serde::Serialize;
 use serde_json::json;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::path::Path;
 
 pub struct Network {
     graph: Graph<(), (), Undirected>,
+    /// Node degrees, computed once as nodes query them in every step
+    degrees: Vec<usize>,
     name: String,
     conf: serde_json::Value,
+    attributes: BTreeMap<String, Vec<serde_json::Value>>,
 }
 
 #[derive(Serialize)]
@@ -20,6 +23,9 @@ pub struct NetworkDescription<'a> {
     pub edges: usize,
     #[serde(flatten)]
     pub conf: &'a serde_json::Value,
+    /// Node attributes, on which node selections of the configuration may depend
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: &'a BTreeMap<String, Vec<serde_json::Value>>,
 }
 
 impl Network {
@@ -38,27 +44,27 @@ impl Network {
             let node2 = *nodes.entry(n2).or_insert_with(|| graph.add_node(()));
             graph.add_edge(node1, node2, ());
         }
-        Network {
+        Network::from_graph(
             graph,
-            name: path.to_string_lossy().to_string(),
-            conf: serde_json::Value::Null,
-        }
+            path.to_string_lossy().to_string(),
+            serde_json::Value::Null,
+        )
     }
 
     pub fn line(size: u32) -> Network {
-        Network {
-            graph: Self::_make_grid(1, size),
-            name: "line".to_string(),
-            conf: serde_json::Value::Null,
-        }
+        Network::from_graph(
+            Self::_make_grid(1, size),
+            "line".to_string(),
+            serde_json::Value::Null,
+        )
     }
 
     pub fn grid(size_x: u32, size_y: u32) -> Network {
-        Network {
-            graph: Self::_make_grid(size_x, size_y),
-            name: "grid".to_string(),
-            conf: json!({ "x": size_x, "y": size_y }),
-        }
+        Network::from_graph(
+            Self::_make_grid(size_x, size_y),
+            "grid".to_string(),
+            json!({ "x": size_x, "y": size_y }),
+        )
     }
 
     pub fn random(rng: &mut impl Rng, n_nodes: u32, prob: f64) -> Network {
@@ -78,10 +84,24 @@ impl Network {
             .map(char::from)
             .collect();
 
+        Network::from_graph(graph, "rnd".to_string(), json!({"p": prob, "uid": uid }))
+    }
+
+    fn from_graph(
+        graph: Graph<(), (), Undirected>,
+        name: String,
+        conf: serde_json::Value,
+    ) -> Network {
+        let degrees = graph
+            .node_indices()
+            .map(|node| graph.neighbors(node).count())
+            .collect();
         Network {
             graph,
-            name: "rnd".to_string(),
-            conf: json!({"p": prob, "uid": uid }),
+            degrees,
+            name,
+            conf,
+            attributes: BTreeMap::new(),
         }
     }
 
@@ -111,15 +131,26 @@ impl Network {
         graph
     }
 
-    pub fn description(&self) -> NetworkDescription {
+    pub fn description(&self) -> NetworkDescription<'_> {
         NetworkDescription {
             name: &self.name,
             nodes: self.graph.node_count(),
             edges: self.graph.edge_count(),
             conf: &self.conf,
+            attributes: &self.attributes,
         }
     }
 
+    /// Attaches a named attribute (one value per node), e.g. a group label
+    pub fn set_node_attribute(&mut self, name: &str, values: Vec<serde_json::Value>) {
+        assert_eq!(values.len(), self.node_count());
+        self.attributes.insert(name.to_string(), values);
+    }
+
+    pub fn node_attribute(&self, name: &str) -> Option<&[serde_json::Value]> {
+        self.attributes.get(name).map(|v| v.as_slice())
+    }
+
     pub fn name(&self) -> &str {
         &self.name
     }
@@ -135,6 +166,10 @@ impl Network {
     pub fn edge_count(&self) -> usize {
         self.graph.edge_count()
     }
+
+    pub fn degree(&self, node: usize) -> usize {
+        self.degrees[node]
+    }
 }
 
 #[cfg(test)]
@@ -161,5 +196,7 @@ mod test {
         assert_eq!(net.graph.edges(4.into()).count(), 4);
         assert_eq!(net.graph.edges(10.into()).count(), 3);
         assert_eq!(net.graph.edges(11.into()).count(), 2);
+        let degrees: Vec<_> = (0..net.node_count()).map(|node| net.degree(node)).collect();
+        assert_eq!(degrees, [2, 3, 2, 3, 4, 3, 3, 4, 3, 2, 3, 2]);
     }
 }
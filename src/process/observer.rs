@@ -0,0 +1,80 @@
+use crate::process::intervention::Intervention;
+use crate::process::process::Process;
+use crate::process::report::RunReport;
+use crate::process::state::State;
+use ndarray::Array2;
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObserverAction {
+    Continue,
+    /// Terminates the run after the current step
+    Stop,
+}
+
+/// Read-only view of a simulator passed to observers
+pub struct Observation<'a, ProcessT: Process> {
+    pub seed: u64,
+    pub step: usize,
+    pub state: &'a State<ProcessT>,
+    /// Per node action counts of the current window (nodes x actions)
+    pub action_counts: &'a Array2<u64>,
+    /// Generator of sequential steps, stored in traces to re-simulate from a state frame
+    pub(crate) rng: &'a Xoshiro256PlusPlus,
+}
+
+/// Hooks into a running `Simulator`, see `Simulator::add_observer`.
+pub trait Observer<ProcessT: Process> {
+    /// Called with the initial state (step 0) and after every step
+    fn on_step(&mut self, _observation: &Observation<ProcessT>) -> ObserverAction {
+        ObserverAction::Continue
+    }
+
+    /// Called when a window of action counts is finished, i.e. at the end of bootstrap,
+    /// at the end of each measurement window and when an absorbing state is detected
+    fn on_window(&mut self, _observation: &Observation<ProcessT>) -> ObserverAction {
+        ObserverAction::Continue
+    }
+
+    /// Called after an intervention is applied, `nodes` are the affected nodes
+    /// (empty for payoff changes)
+    fn on_intervention(
+        &mut self,
+        _observation: &Observation<ProcessT>,
+        _intervention: &Intervention,
+        _nodes: &[usize],
+    ) {
+    }
+
+    /// Called before a checkpoint is written
+    fn on_checkpoint(&mut self, _observation: &Observation<ProcessT>) {}
+
+    fn on_termination(&mut self, _observation: &Observation<ProcessT>, _report: &RunReport) {}
+}
+
+impl<ProcessT: Process, ObserverT: Observer<ProcessT>> Observer<ProcessT> for &mut ObserverT {
+    fn on_step(&mut self, observation: &Observation<ProcessT>) -> ObserverAction {
+        (**self).on_step(observation)
+    }
+
+    fn on_window(&mut self, observation: &Observation<ProcessT>) -> ObserverAction {
+        (**self).on_window(observation)
+    }
+
+    fn on_intervention(
+        &mut self,
+        observation: &Observation<ProcessT>,
+        intervention: &Intervention,
+        nodes: &[usize],
+    ) {
+        (**self).on_intervention(observation, intervention, nodes)
+    }
+
+    fn on_checkpoint(&mut self, observation: &Observation<ProcessT>) {
+        (**self).on_checkpoint(observation)
+    }
+
+    fn on_termination(&mut self, observation: &Observation<ProcessT>, report: &RunReport) {
+        (**self).on_termination(observation, report)
+    }
+}
@@ -1,21 +1,324 @@
 use crate::games::game::ActionId;
 use crate::process::network::Network;
 use crate::process::state::State;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-pub trait Process: Sized {
-    type NodeStateT: Serialize;
-    const ACTIONS: usize;
+/// Information about the node being updated in `Process::node_step`
+#[derive(Clone, Copy)]
+pub struct NodeContext<'a> {
+    pub node: usize,
+    pub degree: usize,
+    /// Number of the step being computed (the first step is 1)
+    pub step: usize,
+    pub network: &'a Network,
+}
+
+impl<'a> NodeContext<'a> {
+    pub fn new(network: &'a Network, node: usize, step: usize) -> Self {
+        NodeContext {
+            node,
+            degree: network.degree(node),
+            step,
+            network,
+        }
+    }
+
+    /// Value of a network attribute for this node
+    pub fn attribute(&self, name: &str) -> Option<&'a serde_json::Value> {
+        self.network
+            .node_attribute(name)
+            .map(|values| &values[self.node])
+    }
+}
+
+/// Dynamics of node states and actions. Payoff interventions change a copy of the process
+/// (the one given to the simulator is kept), hence `Clone`.
+pub trait Process: Sized + Sync + Clone {
+    type NodeStateT: Serialize + DeserializeOwned + Clone + Send + Sync;
+    /// Public information that neighbors of a node receive
+    type SignalT: Clone + Send + Sync;
+
+    /// Number of actions available to each node
+    fn actions(&self) -> usize;
+
+    /// Fails if the process cannot run on `network`, e.g. a node selection does not resolve;
+    /// `make_initial_state` may panic otherwise
+    fn validate(&self, _network: &Network) -> Result<(), String> {
+        Ok(())
+    }
 
     fn make_initial_state(&self, rng: &mut impl rand::Rng, network: &Network) -> State<Self>;
 
+    /// Signal emitted by a node with the given state that played `action` in the last step
+    fn signal(&self, node_state: &Self::NodeStateT, action: ActionId) -> Self::SignalT;
+
     fn node_step(
         &self,
         rng: &mut impl rand::Rng,
+        context: &NodeContext,
         node_state: &Self::NodeStateT,
         last_action: ActionId,
-        neighbors: impl Iterator<Item = ActionId>,
+        neighbors: impl Iterator<Item = Self::SignalT>,
     ) -> (Self::NodeStateT, ActionId);
 
     fn configuration(&self) -> serde_json::Value;
+
+    /// Names of node groups of a heterogeneous population, empty for a homogeneous one
+    fn groups(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Index into `groups` of a node with the given state
+    fn node_group(&self, _node_state: &Self::NodeStateT) -> usize {
+        0
+    }
+
+    /// Rows of the payoff matrix, `None` if the process does not have one
+    fn payoff_matrix(&self) -> Option<Vec<Vec<f32>>> {
+        None
+    }
+
+    /// Replaces the payoff matrix; the simulator passes only matrices of the size returned by
+    /// `payoff_matrix` and never calls it when that is `None` (see `SimulatorConfig::validate_for`)
+    fn set_payoff_matrix(&mut self, _payoff_matrix: &[Vec<f32>]) {
+        panic!("Process does not have a payoff matrix");
+    }
+
+    /// Adds `delta` to the payoff matrix, by default via `payoff_matrix` and `set_payoff_matrix`;
+    /// the same preconditions as for `set_payoff_matrix` apply
+    fn add_payoffs(&mut self, delta: &[Vec<f32>]) {
+        let mut matrix = self
+            .payoff_matrix()
+            .expect("Process does not have a payoff matrix");
+        assert_eq!(matrix.len(), delta.len());
+        for (row, delta_row) in matrix.iter_mut().zip(delta) {
+            for (value, d) in row.iter_mut().zip(delta_row) {
+                *value += d;
+            }
+        }
+        self.set_payoff_matrix(&matrix);
+    }
+
+    /// Whether a node state changed in a way that matters for absorption detection
+    /// (see `SimulatorConfig::set_absorbing_steps`). By default states are ignored and only
+    /// actions are compared, as e.g. action counts change in every step.
+    fn node_state_changed(&self, _previous: &Self::NodeStateT, _next: &Self::NodeStateT) -> bool {
+        false
+    }
+
+    /// Moves a node state towards its initial value, `strength` 0 keeps it, 1 resets it
+    fn perturb_node_state(
+        &self,
+        _rng: &mut impl rand::Rng,
+        _node_state: &mut Self::NodeStateT,
+        _strength: f32,
+    ) {
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::game::ActionId;
+    use crate::process::network::Network;
+    use crate::process::process::{NodeContext, Process};
+    use crate::process::report::TerminationReason;
+    use crate::process::simulator::{Simulator, SimulatorConfig};
+    use crate::process::state::State;
+    use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Remembers the context of its last step
+    #[derive(Clone)]
+    struct ContextProcess;
+
+    impl Process for ContextProcess {
+        type NodeStateT = (usize, usize, usize, Option<Value>);
+        type SignalT = ActionId;
+
+        fn actions(&self) -> usize {
+            2
+        }
+
+        fn make_initial_state(&self, _rng: &mut impl rand::Rng, network: &Network) -> State<Self> {
+            State::new_by(network, || ((0, 0, 0, None), 0))
+        }
+
+        fn signal(&self, _node_state: &Self::NodeStateT, action: ActionId) -> ActionId {
+            action
+        }
+
+        fn node_step(
+            &self,
+            _rng: &mut impl rand::Rng,
+            context: &NodeContext,
+            _node_state: &Self::NodeStateT,
+            _last_action: ActionId,
+            _neighbors: impl Iterator<Item = ActionId>,
+        ) -> (Self::NodeStateT, ActionId) {
+            let state = (
+                context.node,
+                context.degree,
+                context.step,
+                context.attribute("label").cloned(),
+            );
+            (state, context.degree % 2)
+        }
+
+        fn configuration(&self) -> Value {
+            json!({"game": "context"})
+        }
+    }
+
+    #[test]
+    fn test_node_context() {
+        let mut network = Network::line(3);
+        network.set_node_attribute("label", vec![json!("a"), json!("b"), json!("c")]);
+        let run = |config: &SimulatorConfig| {
+            let mut simulator = Simulator::new(config, None, &network, &ContextProcess);
+            simulator.step();
+            simulator.step();
+            (
+                simulator.state().node_states().to_vec(),
+                simulator.state().last_actions().to_vec(),
+            )
+        };
+        let mut config = SimulatorConfig::new();
+        let (states, actions) = run(&config);
+        assert_eq!(
+            states,
+            vec![
+                (0, 1, 2, Some(json!("a"))),
+                (1, 2, 2, Some(json!("b"))),
+                (2, 1, 2, Some(json!("c"))),
+            ]
+        );
+        assert_eq!(actions, vec![1, 0, 1]);
+        config.set_parallel(true);
+        assert_eq!(run(&config), (states, actions));
+    }
+
+    /// Continuous opinions averaged over neighbors, the action is the rounded opinion
+    #[derive(Clone)]
+    struct OpinionProcess {
+        opinions: Vec<f32>,
+        signal_calls: Arc<AtomicUsize>,
+    }
+
+    impl Process for OpinionProcess {
+        type NodeStateT = f32;
+        type SignalT = f32;
+
+        fn actions(&self) -> usize {
+            2
+        }
+
+        fn make_initial_state(&self, _rng: &mut impl rand::Rng, _network: &Network) -> State<Self> {
+            let actions = self
+                .opinions
+                .iter()
+                .map(|o| o.round() as ActionId)
+                .collect();
+            State::new(self.opinions.clone(), actions)
+        }
+
+        fn signal(&self, node_state: &f32, _action: ActionId) -> f32 {
+            self.signal_calls.fetch_add(1, Ordering::Relaxed);
+            *node_state
+        }
+
+        fn node_step(
+            &self,
+            _rng: &mut impl rand::Rng,
+            context: &NodeContext,
+            _node_state: &f32,
+            _last_action: ActionId,
+            neighbors: impl Iterator<Item = f32>,
+        ) -> (f32, ActionId) {
+            let opinion = neighbors.sum::<f32>() / context.degree as f32;
+            (opinion, opinion.round() as ActionId)
+        }
+
+        fn configuration(&self) -> Value {
+            json!({"game": "opinion"})
+        }
+    }
+
+    #[test]
+    fn test_signal() {
+        let network = Network::line(4);
+        let process = OpinionProcess {
+            opinions: vec![0.0, 0.0, 1.0, 1.0],
+            signal_calls: Arc::new(AtomicUsize::new(0)),
+        };
+        let mut config = SimulatorConfig::new();
+        for parallel in [false, true] {
+            config.set_parallel(parallel);
+            process.signal_calls.store(0, Ordering::Relaxed);
+            let mut simulator = Simulator::new(&config, None, &network, &process);
+            simulator.step();
+            assert_eq!(simulator.state().node_states(), [0.0, 0.5, 0.5, 1.0]);
+            simulator.step();
+            assert_eq!(simulator.state().node_states(), [0.5, 0.25, 0.75, 0.5]);
+            assert_eq!(simulator.state().last_actions(), [1, 0, 1, 1]);
+            // One signal per node and step, not per edge
+            assert_eq!(process.signal_calls.load(Ordering::Relaxed), 2 * 4);
+        }
+    }
+
+    /// Counts steps up to 5, the action never changes
+    #[derive(Clone)]
+    struct SettlingProcess;
+
+    impl Process for SettlingProcess {
+        type NodeStateT = usize;
+        type SignalT = ActionId;
+
+        fn actions(&self) -> usize {
+            2
+        }
+
+        fn make_initial_state(&self, _rng: &mut impl rand::Rng, network: &Network) -> State<Self> {
+            State::new_by(network, || (0, 0))
+        }
+
+        fn signal(&self, _node_state: &usize, action: ActionId) -> ActionId {
+            action
+        }
+
+        fn node_step(
+            &self,
+            _rng: &mut impl rand::Rng,
+            context: &NodeContext,
+            _node_state: &usize,
+            _last_action: ActionId,
+            _neighbors: impl Iterator<Item = ActionId>,
+        ) -> (usize, ActionId) {
+            (context.step.min(5), 0)
+        }
+
+        fn configuration(&self) -> Value {
+            json!({"game": "settling"})
+        }
+
+        fn node_state_changed(&self, previous: &usize, next: &usize) -> bool {
+            previous != next
+        }
+    }
+
+    #[test]
+    fn test_absorption_waits_for_states() {
+        let network = Network::line(3);
+        let mut config = SimulatorConfig::new();
+        config.set_absorbing_steps(3);
+        for parallel in [false, true] {
+            config.set_parallel(parallel);
+            let mut simulator = Simulator::new(&config, None, &network, &SettlingProcess);
+            assert_eq!(simulator.run(), TerminationReason::Absorbed);
+            let report = simulator.report();
+            assert_eq!(report.fixation.unwrap().step, 5);
+            assert_eq!(report.steps, 8);
+        }
+    }
 }
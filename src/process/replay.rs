@@ -0,0 +1,295 @@
+use crate::games::game::ActionId;
+use crate::process::network::Network;
+use crate::process::process::Process;
+use crate::process::simulator::{Simulator, SimulatorConfig};
+use crate::process::state::State;
+use crate::process::trace::{TraceReader, TraceRecord};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+
+/// Reconstructs configurations of a traced run at arbitrary steps.
+///
+/// A step stored in the trace (as a full state or a delta frame) is returned directly. Otherwise the run is re-simulated
+/// with the recorded seed from the nearest stored state before the step; state frames contain
+/// the generator of sequential steps and in parallel mode random streams depend only on the seed,
+/// step and node. Without a usable state frame the run is re-simulated from the beginning.
+/// `config`, `network` and `process` have to be the same as in the traced run.
+pub struct Replay<'a, ProcessT: Process> {
+    path: PathBuf,
+    config: SimulatorConfig,
+    network: &'a Network,
+    process: &'a ProcessT,
+}
+
+impl<'a, ProcessT: Process> Replay<'a, ProcessT> {
+    pub fn new(
+        path: &Path,
+        config: &SimulatorConfig,
+        network: &'a Network,
+        process: &'a ProcessT,
+    ) -> Self {
+        let mut config = config.clone();
+        config.disable_outputs();
+        Replay {
+            path: path.into(),
+            config,
+            network,
+            process,
+        }
+    }
+
+    pub fn action_profile_at(&self, step: usize) -> std::io::Result<Vec<ActionId>> {
+        let scan = self.scan(step)?;
+        if let Some(actions) = scan.actions {
+            return Ok(actions);
+        }
+        Ok(self.simulate(step, scan)?.last_actions().to_vec())
+    }
+
+    pub fn state_at(&self, step: usize) -> std::io::Result<State<ProcessT>> {
+        let mut scan = self.scan(step)?;
+        if let Some(state) = scan.state.take() {
+            return Ok(state);
+        }
+        self.simulate(step, scan)
+    }
+
+    /// Collects frames usable for reconstruction of `step`. Frames of a trace
+    /// restricted to a subset of nodes cannot be used, only its seed.
+    fn scan(&self, step: usize) -> std::io::Result<Scan<ProcessT>> {
+        let mut scan = Scan {
+            seed: None,
+            actions: None,
+            state: None,
+            nearest: None,
+        };
+        let mut partial = false;
+        let mut actions: Option<Vec<ActionId>> = None;
+        for record in TraceReader::<ProcessT::NodeStateT>::open(&self.path)? {
+            match record? {
+                TraceRecord::Start(start) => {
+                    scan.seed = Some(start.seed);
+                    partial = start.traced.is_some();
+                }
+                TraceRecord::State(frame) if !partial && frame.step <= step => {
+                    actions = Some(frame.actions.clone());
+                    let state = State::new(frame.states, frame.actions);
+                    if frame.step == step {
+                        scan.actions = actions;
+                        scan.state = Some(state);
+                        break;
+                    }
+                    scan.nearest = Some((frame.step, state, frame.rng));
+                }
+                TraceRecord::Delta(frame) if !partial && frame.step <= step => {
+                    if let Some(actions) = &mut actions {
+                        for (node, action) in frame.nodes.iter().zip(frame.actions) {
+                            actions[*node] = action;
+                        }
+                        if frame.step == step {
+                            scan.actions = Some(actions.clone());
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(scan)
+    }
+
+    fn simulate(&self, step: usize, scan: Scan<ProcessT>) -> std::io::Result<State<ProcessT>> {
+        let seed = scan
+            .seed
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "trace does not contain a seed"))?;
+        let mut simulator = Simulator::with_seed(&self.config, seed, self.network, self.process);
+        if let Some((stored_step, state, rng)) = scan.nearest {
+            if rng.is_some() || self.config.is_parallel() {
+                simulator.restore_state(stored_step, state, rng);
+            }
+        }
+        while simulator.current_step() < step {
+            simulator.step();
+        }
+        Ok(simulator.state().clone())
+    }
+}
+
+struct Scan<ProcessT: Process> {
+    seed: Option<u64>,
+    /// Action profile at the requested step, if stored
+    actions: Option<Vec<ActionId>>,
+    /// State at the requested step, if stored
+    state: Option<State<ProcessT>>,
+    /// The last stored state before the requested step, with the generator of sequential steps
+    nearest: Option<(usize, State<ProcessT>, Option<Xoshiro256PlusPlus>)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::counting::PlayerState;
+    use crate::process::fixarray::IntArray;
+    use crate::process::network::Network;
+    use crate::process::replay::Replay;
+    use crate::process::simulator::Simulator;
+    use crate::process::testing::{coordination_process, windows_config};
+    use crate::process::trace::{TraceFilter, TraceFormat, TraceMode, TraceReader, TraceRecord};
+
+    fn check_replay(parallel: bool, name: &str) {
+        let game = coordination_process();
+        let network = Network::grid(4, 4);
+        let path = std::env::temp_dir().join(format!("pon-test-replay-{}.json", name));
+        let mut config = windows_config(100, 2);
+        config.set_parallel(parallel);
+        config.set_trace_path(&path);
+        config.set_report_state_step(50);
+        {
+            let mut simulator = Simulator::with_seed(&config, 99, &network, &game);
+            simulator.run();
+        }
+
+        let mut plain_config = config.clone();
+        plain_config.disable_outputs();
+        let mut simulator = Simulator::with_seed(&plain_config, 99, &network, &game);
+        let replay = Replay::new(&path, &config, &network, &game);
+        for step in [0, 37, 50, 120, 299] {
+            while simulator.current_step() < step {
+                simulator.step();
+            }
+            assert_eq!(
+                replay.action_profile_at(step).unwrap(),
+                simulator.state().last_actions()
+            );
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_sequential() {
+        check_replay(false, "sequential");
+    }
+
+    #[test]
+    fn test_replay_parallel() {
+        check_replay(true, "parallel");
+    }
+
+    #[test]
+    fn test_replay_from_state_frame() {
+        let game = coordination_process();
+        let network = Network::grid(4, 4);
+        let path = std::env::temp_dir().join("pon-test-replay-state-frame.json");
+        let mut config = windows_config(100, 1);
+        config.set_trace_path(&path);
+        config.set_report_state_step(50);
+        {
+            let mut simulator = Simulator::with_seed(&config, 99, &network, &game);
+            simulator.run();
+        }
+        // A re-simulation from step 0 with the changed seed diverges from the traced run
+        let trace = std::fs::read_to_string(&path).unwrap();
+        assert!(trace.contains("\"seed\":99,"));
+        std::fs::write(&path, trace.replace("\"seed\":99,", "\"seed\":98,")).unwrap();
+
+        let mut plain_config = config.clone();
+        plain_config.disable_outputs();
+        let mut simulator = Simulator::with_seed(&plain_config, 99, &network, &game);
+        let replay = Replay::new(&path, &config, &network, &game);
+        for step in [120, 199] {
+            while simulator.current_step() < step {
+                simulator.step();
+            }
+            assert_eq!(
+                replay.state_at(step).unwrap().last_actions(),
+                simulator.state().last_actions()
+            );
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_selective_trace() {
+        let game = coordination_process();
+        let network = Network::grid(4, 4);
+
+        let run = |filter: TraceFilter, name: &str| {
+            let path = std::env::temp_dir().join(format!("pon-test-filter-{}.bin", name));
+            let mut config = windows_config(100, 1);
+            config.set_trace_path(&path);
+            config.set_trace_format(TraceFormat::Binary);
+            config.set_report_state_step(10);
+            config.set_trace_filter(filter);
+            {
+                let mut simulator = Simulator::with_seed(&config, 3, &network, &game);
+                simulator.run();
+            }
+            let records: Vec<TraceRecord<PlayerState<IntArray<2>>>> = TraceReader::open(&path)
+                .unwrap()
+                .collect::<std::io::Result<_>>()
+                .unwrap();
+            (path, config, records)
+        };
+
+        let (path, config, records) = run(
+            TraceFilter {
+                mode: TraceMode::Delta,
+                ..Default::default()
+            },
+            "delta",
+        );
+        assert!(matches!(records[1], TraceRecord::State(_)));
+        assert_eq!(
+            records
+                .iter()
+                .filter(|r| matches!(r, TraceRecord::Delta(_)))
+                .count(),
+            20
+        );
+        let replay = Replay::new(&path, &config, &network, &game);
+        let mut plain_config = config.clone();
+        plain_config.disable_outputs();
+        let mut simulator = Simulator::with_seed(&plain_config, 3, &network, &game);
+        for step in [70, 200] {
+            while simulator.current_step() < step {
+                simulator.step();
+            }
+            assert_eq!(
+                replay.action_profile_at(step).unwrap(),
+                simulator.state().last_actions()
+            );
+        }
+        std::fs::remove_file(path).unwrap();
+
+        let (path, _, records) = run(
+            TraceFilter {
+                nodes: Some(vec![1, 5]),
+                log_steps_per_decade: Some(2),
+                mode: TraceMode::Summary,
+            },
+            "summary",
+        );
+        let steps: Vec<_> = records
+            .iter()
+            .filter_map(|r| match r {
+                TraceRecord::Summary(s) => {
+                    assert_eq!(s.counts.iter().sum::<u64>(), 2);
+                    Some(s.step)
+                }
+                TraceRecord::Window(w) => {
+                    assert_eq!(w.counts.len(), 2);
+                    None
+                }
+                TraceRecord::State(_) | TraceRecord::Delta(_) | TraceRecord::Intervention(_) => {
+                    unreachable!()
+                }
+                TraceRecord::Start(s) => {
+                    assert_eq!(s.traced, Some(vec![1, 5]));
+                    None
+                }
+            })
+            .collect();
+        assert_eq!(steps, vec![0, 1, 4, 10, 32, 100]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,9 +1,120 @@
+use crate::games::game::ActionId;
 use ndarray::Array2;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Debug)]
 pub struct RunReport {
+    pub seed: u64,
     pub steps: usize,
-    pub converged: bool,
+    /// `None` if the run has not finished yet
+    pub termination: Option<TerminationReason>,
+    /// Step at which measurement windows started, `None` while still in burn-in
+    pub burn_in_steps: Option<usize>,
     pub avg_policy: Array2<f32>,
+    /// Indices of zealot nodes; their rows of `avg_policy` are fixed
+    pub zealots: Vec<usize>,
+    /// Mean policies of node groups of a heterogeneous process (empty otherwise)
+    pub groups: Vec<GroupPolicy>,
+    pub fixation: Option<Fixation>,
+    pub series: Option<TimeSeries>,
+    /// Error of the last failed periodic checkpoint; a failed write does not stop the run
+    pub checkpoint_error: Option<String>,
+    pub details: Option<RunDetails>,
+    pub configuration: RunConfiguration,
+}
+
+/// Everything needed to rerun a simulation: together with `RunReport::seed`
+/// it reproduces the run.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunConfiguration {
+    pub process: serde_json::Value,
+    pub network: serde_json::Value,
+    pub simulator: serde_json::Value,
+}
+
+/// Mean policy of nodes of one group, zealots excluded
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GroupPolicy {
+    pub name: String,
+    pub nodes: usize,
+    pub policy: Vec<f32>,
+}
+
+/// Why a run ended. Except for `Converged` and `Absorbed` the report is partial:
+/// `avg_policy` is the policy of the last finished window.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminationReason {
+    Converged,
+    /// No action or node state changed for `absorbing_steps` steps
+    Absorbed,
+    MaxWindows,
+    /// `max_steps` budget was exhausted
+    StepBudget,
+    /// `max_wall_time` budget was exhausted
+    TimeBudget,
+    /// Requested by an observer
+    Stopped,
+    /// Parallel chains agree, see `diagnostics::GelmanRubin`
+    RHat,
+}
+
+impl RunReport {
+    /// Mean policy of all nodes except zealots, empty if all nodes are zealots
+    pub fn population_policy(&self) -> Vec<f32> {
+        let mut sums = vec![0.0f32; self.avg_policy.ncols()];
+        let mut count = 0;
+        for (node, row) in self.avg_policy.rows().into_iter().enumerate() {
+            if self.zealots.binary_search(&node).is_err() {
+                sums.iter_mut().zip(row).for_each(|(s, p)| *s += p);
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return Vec::new();
+        }
+        sums.iter_mut().for_each(|s| *s /= count as f32);
+        sums
+    }
+
+    pub fn converged(&self) -> bool {
+        matches!(
+            self.termination,
+            Some(
+                TerminationReason::Converged
+                    | TerminationReason::Absorbed
+                    | TerminationReason::RHat
+            )
+        )
+    }
+}
+
+/// Absorbing configuration: no action (or tracked node state) has changed since `step`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fixation {
+    pub step: usize,
+    pub actions: Vec<ActionId>,
+}
+
+/// Population-level trajectory of a run, see `SimulatorConfig::set_series_step`
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TimeSeries {
+    pub steps: Vec<usize>,
+    pub action_fractions: Vec<Vec<f32>>,
+    /// Number of action changes since the previous sample
+    pub changed_nodes: Vec<usize>,
+    pub window_steps: Vec<usize>,
+    pub window_deltas: Vec<f32>,
+}
+
+/// Per-node information, see `SimulatorConfig::set_report_details`
+#[derive(Serialize, Debug)]
+pub struct RunDetails {
+    /// Final actions
+    pub actions: Vec<ActionId>,
+    /// Final node states as serialized by the process
+    pub node_states: serde_json::Value,
+    /// Action counts of nodes since the end of bootstrap (nodes x actions)
+    pub action_counts: Array2<u64>,
+    /// Largest change of each node's policy in the last window
+    pub node_deltas: Vec<f32>,
 }
@@ -1,61 +1,93 @@
+use crate::env::config::{load_config, update_from_args, ConfigError};
+use crate::games::game::ActionId;
+use crate::process::burnin::{BurnIn, BurnInDetector};
+use crate::process::convergence::{Convergence, ConvergenceCriterion};
+use crate::process::intervention::{Intervention, InterventionKind, NodeSelection, Zealots};
 use crate::process::network::Network;
-use crate::process::process::Process;
-use crate::process::report::RunReport;
+use crate::process::observer::{Observation, Observer, ObserverAction};
+use crate::process::process::{NodeContext, Process};
+use crate::process::report::{
+    Fixation, GroupPolicy, RunConfiguration, RunDetails, RunReport, TerminationReason, TimeSeries,
+};
 use crate::process::state::State;
-use crate::process::utils::max_of_array;
+use crate::process::trace::{TraceFilter, TraceFormat, TraceWriter};
+use crate::process::utils::node_rng;
 use ndarray::{Array2, Axis};
-use rand::rngs::{SmallRng, ThreadRng};
-use rand::SeedableRng;
-use serde::Serialize;
+use rand::rngs::ThreadRng;
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelExtend,
+    ParallelIterator,
+};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::fs::File;
 use std::io::BufWriter;
-use std::io::Write;
+use std::io::{BufReader, Write};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SimulatorConfig {
     bootstrap_steps: usize,
+    burn_in: BurnIn,
     window_steps: usize,
     max_windows: usize,
     termination_threshold: f32,
+    convergence: Convergence,
     trace_path: Option<PathBuf>,
+    trace_format: TraceFormat,
+    trace_filter: TraceFilter,
     report_state_step: usize,
+    parallel: bool,
+    absorbing_steps: usize,
+    checkpoint_path: Option<PathBuf>,
+    checkpoint_step: usize,
+    series_step: usize,
+    report_details: bool,
+    max_steps: usize,
+    max_wall_time: Option<f64>,
+    interventions: Vec<Intervention>,
+    zealots: Vec<Zealots>,
 }
 
-#[derive(Serialize)]
-#[serde(tag = "evt")]
-pub enum TraceFrame<'a, ProcessT: Process> {
-    State(StateTraceFrame<'a, ProcessT::NodeStateT>),
-    Window(WindowTraceFrame<'a>),
-}
-
-#[derive(Serialize)]
-pub struct StateTraceFrame<'a, NodeStateT: Serialize> {
-    step: usize,
-    states: &'a [NodeStateT],
-}
-
-#[derive(Serialize)]
-pub struct WindowTraceFrame<'a> {
-    step: usize,
-    actions: usize,
-    counts: &'a [u64],
-}
+const DEFAULT_SEED: u64 = 0b1110110001110101011000111101; // Doc says that SmallRng should enough 1 in seed
 
 impl SimulatorConfig {
     pub fn new() -> Self {
         SimulatorConfig {
             bootstrap_steps: 5000,
+            burn_in: BurnIn::default(),
             window_steps: 200,
             max_windows: 1000,
             termination_threshold: 0.001,
+            convergence: Convergence::default(),
             trace_path: None,
+            trace_format: TraceFormat::default(),
+            trace_filter: TraceFilter::default(),
             report_state_step: 0,
+            parallel: false,
+            absorbing_steps: 0,
+            checkpoint_path: None,
+            checkpoint_step: 0,
+            series_step: 0,
+            report_details: false,
+            max_steps: 0,
+            max_wall_time: None,
+            interventions: Vec::new(),
+            zealots: Vec::new(),
         }
     }
 
     pub fn set_bootstrap_steps(&mut self, bootstrap_steps: usize) {
         self.bootstrap_steps = bootstrap_steps;
     }
+    /// Adaptive burn-in, see `BurnIn`
+    pub fn set_burn_in(&mut self, burn_in: BurnIn) {
+        self.burn_in = burn_in;
+    }
     pub fn set_window_steps(&mut self, window_steps: usize) {
         self.window_steps = window_steps;
     }
@@ -65,12 +97,269 @@ impl SimulatorConfig {
     pub fn set_termination_threshold(&mut self, termination_threshold: f32) {
         self.termination_threshold = termination_threshold;
     }
+    pub fn set_convergence(&mut self, convergence: Convergence) {
+        self.convergence = convergence;
+    }
     pub fn set_trace_path(&mut self, trace_path: &Path) {
         self.trace_path = Some(trace_path.into());
     }
+    pub fn set_trace_format(&mut self, trace_format: TraceFormat) {
+        self.trace_format = trace_format;
+    }
+    pub fn set_trace_filter(&mut self, trace_filter: TraceFilter) {
+        self.trace_filter = trace_filter;
+    }
     pub fn set_report_state_step(&mut self, report_step: usize) {
         self.report_state_step = report_step;
     }
+    /// Nodes are updated by rayon workers, each node draws from its own random stream
+    /// derived from the run seed, hence results do not depend on the number of threads.
+    /// Note that a parallel run produces different (but equally valid) results than a sequential one.
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+    /// Run is terminated when no node changes its action or its state for `absorbing_steps`
+    /// steps, 0 disables the detection. States are compared by `Process::node_state_changed`.
+    pub fn set_absorbing_steps(&mut self, absorbing_steps: usize) {
+        self.absorbing_steps = absorbing_steps;
+    }
+    /// Simulator state is saved into `path` every `checkpoint_step` steps,
+    /// a killed run can be continued by `Simulator::resume`.
+    pub fn set_checkpoint(&mut self, path: &Path, checkpoint_step: usize) {
+        self.checkpoint_path = Some(path.into());
+        self.checkpoint_step = checkpoint_step;
+    }
+    /// Population action fractions and number of action changes since the previous sample
+    /// are recorded into `RunReport::series` every `series_step` steps, together with per-window convergence deltas.
+    /// 0 disables the recording.
+    pub fn set_series_step(&mut self, series_step: usize) {
+        self.series_step = series_step;
+    }
+    /// Includes final state, raw action counts and per-node deltas into `RunReport::details`
+    pub fn set_report_details(&mut self, report_details: bool) {
+        self.report_details = report_details;
+    }
+
+    /// Run ends with `TerminationReason::StepBudget` after `max_steps` steps
+    /// (including bootstrap), 0 means no limit.
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = max_steps;
+    }
+    /// Run ends with `TerminationReason::TimeBudget` when the simulator runs longer
+    /// than `max_wall_time` since its first `run` (or `run_until`) call.
+    pub fn set_max_wall_time(&mut self, max_wall_time: Duration) {
+        self.max_wall_time = Some(max_wall_time.as_secs_f64());
+    }
+
+    /// Schedules an intervention; interventions of step `t` are applied to the state
+    /// after step `t` (i.e. as seen by observers and traces at `t`), in the order of addition.
+    pub fn add_intervention(&mut self, intervention: Intervention) {
+        self.interventions.push(intervention);
+    }
+
+    /// Selected nodes always play `action`; neighbors observe them as usual.
+    /// Zealots are excluded from convergence checks and listed in `RunReport::zealots`.
+    pub fn add_zealots(&mut self, nodes: NodeSelection, action: ActionId) {
+        self.zealots.push(Zealots { nodes, action });
+    }
+
+    pub fn is_parallel(&self) -> bool {
+        self.parallel
+    }
+
+    /// Loads a validated configuration from a JSON or TOML file, see `env::config::load_config`
+    pub fn load(path: &Path, overrides: &[String]) -> Result<Self, ConfigError> {
+        let config: Self = load_config(path, overrides)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Returns a validated copy updated by command line arguments,
+    /// see `env::config::update_from_args`
+    pub fn update_from_args(
+        &self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, ConfigError> {
+        let config = update_from_args(self, args)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let check = |ok: bool, message: &str| {
+            if ok {
+                Ok(())
+            } else {
+                Err(ConfigError::Invalid(message.to_string()))
+            }
+        };
+        check(self.window_steps > 0, "window_steps has to be positive")?;
+        check(self.max_windows > 0, "max_windows has to be positive")?;
+        check(
+            self.termination_threshold.is_finite() && self.termination_threshold >= 0.0,
+            "termination_threshold has to be a non-negative number",
+        )?;
+        check(
+            self.checkpoint_path.is_none() || self.checkpoint_step > 0,
+            "checkpoint_step has to be positive when checkpoint_path is set",
+        )?;
+        check(
+            self.trace_filter.log_steps_per_decade != Some(0),
+            "trace_filter.log_steps_per_decade has to be positive",
+        )?;
+        check(
+            self.max_wall_time.is_none_or(|t| t.is_finite() && t > 0.0),
+            "max_wall_time has to be a positive number of seconds",
+        )?;
+        if let BurnIn::Geweke {
+            sample_step,
+            samples,
+            z_threshold,
+        } = self.burn_in
+        {
+            check(
+                sample_step > 0,
+                "burn_in.Geweke.sample_step has to be positive",
+            )?;
+            check(
+                samples >= 20,
+                "burn_in.Geweke.samples has to be at least 20",
+            )?;
+            check(
+                z_threshold > 0.0,
+                "burn_in.Geweke.z_threshold has to be positive",
+            )?;
+        }
+        for intervention in &self.interventions {
+            validate_intervention(intervention)?;
+        }
+        for zealots in &self.zealots {
+            if let NodeSelection::RandomFraction(f) = zealots.nodes {
+                check(
+                    (0.0..=1.0).contains(&f),
+                    "zealot fraction has to be in [0, 1]",
+                )?;
+            }
+        }
+        validate_convergence(&self.convergence)
+    }
+
+    /// Like `validate`, and checks that the process, interventions and zealots fit
+    /// the network (see `Process::validate`) and interventions and zealots the process
+    pub fn validate_for<ProcessT: Process>(
+        &self,
+        network: &Network,
+        process: &ProcessT,
+    ) -> Result<(), ConfigError> {
+        self.validate()?;
+        process
+            .validate(network)
+            .map_err(|message| ConfigError::Invalid(format!("process: {}", message)))?;
+        for intervention in &self.interventions {
+            validate_intervention_target(intervention, network, process)?;
+        }
+        for zealots in &self.zealots {
+            let invalid =
+                |message: String| Err(ConfigError::Invalid(format!("zealots: {}", message)));
+            if zealots.action >= process.actions() {
+                return invalid(format!(
+                    "action {} is not one of {} actions",
+                    zealots.action,
+                    process.actions()
+                ));
+            }
+            zealots.nodes.validate(network).or_else(invalid)?;
+        }
+        Ok(())
+    }
+
+    /// Disables trace and checkpoints, used for re-simulations of a traced run
+    pub(crate) fn disable_outputs(&mut self) {
+        self.trace_path = None;
+        self.checkpoint_path = None;
+    }
+}
+
+fn validate_intervention(intervention: &Intervention) -> Result<(), ConfigError> {
+    let invalid = |message: &str| {
+        Err(ConfigError::Invalid(format!(
+            "intervention at step {}: {}",
+            intervention.step, message
+        )))
+    };
+    let nodes = match &intervention.kind {
+        InterventionKind::SetPayoffs(matrix) | InterventionKind::AddPayoffs(matrix) => {
+            if matrix.iter().any(|row| row.len() != matrix.len()) {
+                return invalid("payoff matrix has to be square");
+            }
+            return Ok(());
+        }
+        InterventionKind::ForceActions { nodes, .. } => nodes,
+        InterventionKind::PerturbStates { nodes, strength } => {
+            if !(0.0..=1.0).contains(strength) {
+                return invalid("strength has to be in [0, 1]");
+            }
+            nodes
+        }
+    };
+    match nodes {
+        NodeSelection::RandomFraction(f) if !(0.0..=1.0).contains(f) => {
+            invalid("fraction has to be in [0, 1]")
+        }
+        _ => Ok(()),
+    }
+}
+
+fn validate_intervention_target<ProcessT: Process>(
+    intervention: &Intervention,
+    network: &Network,
+    process: &ProcessT,
+) -> Result<(), ConfigError> {
+    let invalid = |message: String| {
+        Err(ConfigError::Invalid(format!(
+            "intervention at step {}: {}",
+            intervention.step, message
+        )))
+    };
+    let nodes = match &intervention.kind {
+        InterventionKind::SetPayoffs(matrix) | InterventionKind::AddPayoffs(matrix) => {
+            let Some(payoff_matrix) = process.payoff_matrix() else {
+                return invalid("process does not have a payoff matrix".to_string());
+            };
+            if matrix.len() != payoff_matrix.len() {
+                return invalid(format!(
+                    "payoff matrix has to be {0}x{0}",
+                    payoff_matrix.len()
+                ));
+            }
+            return Ok(());
+        }
+        InterventionKind::ForceActions { nodes, action } => {
+            if *action >= process.actions() {
+                return invalid(format!(
+                    "action {} is not one of {} actions",
+                    action,
+                    process.actions()
+                ));
+            }
+            nodes
+        }
+        InterventionKind::PerturbStates { nodes, .. } => nodes,
+    };
+    nodes.validate(network).or_else(invalid)
+}
+
+fn validate_convergence(convergence: &Convergence) -> Result<(), ConfigError> {
+    match convergence {
+        Convergence::MovingVariance { windows } if *windows < 2 => Err(ConfigError::Invalid(
+            "convergence.MovingVariance.windows has to be at least 2".to_string(),
+        )),
+        Convergence::Consecutive { windows: 0, .. } => Err(ConfigError::Invalid(
+            "convergence.Consecutive.windows has to be positive".to_string(),
+        )),
+        Convergence::Consecutive { criterion, .. } => validate_convergence(criterion),
+        _ => Ok(()),
+    }
 }
 
 impl Default for SimulatorConfig {
@@ -81,110 +370,654 @@ impl Default for SimulatorConfig {
 
 pub struct Simulator<'a, ProcessT: Process> {
     network: &'a Network,
-    process: &'a ProcessT,
-    rng: SmallRng,
+    /// Process as given by the user, `process` differs from it after payoff interventions
+    initial_process: &'a ProcessT,
+    process: Cow<'a, ProcessT>,
+    seed: u64,
+    rng: Xoshiro256PlusPlus,
     //rng: ThreadRng,
     state: State<ProcessT>,
 
     action_counts: Array2<u64>,
     last_policies: Array2<f32>,
-    converged: bool,
+    node_deltas: Vec<f32>,
+    criterion: Box<dyn ConvergenceCriterion>,
+    termination: Option<TerminationReason>,
     step: usize,
+    last_change_step: usize,
+    fixation: Option<Fixation>,
+    series: Option<TimeSeries>,
+    /// Action changes since the last series sample
+    series_changes: usize,
+    burn_in_detector: BurnInDetector,
+    burn_in_steps: Option<usize>,
+    started: Option<Instant>,
+    /// Fixed action of each node, empty if there are no zealots
+    zealot_actions: Vec<Option<ActionId>>,
+    /// Sorted indices of zealots
+    zealots: Vec<usize>,
+    /// Signals of all nodes in the current step, reused between steps
+    signals: Vec<ProcessT::SignalT>,
 
     config: &'a SimulatorConfig,
-    trace_file: Option<BufWriter<File>>,
+    observers: Vec<Box<dyn Observer<ProcessT> + 'a>>,
+    stop_requested: bool,
+    /// Last failed periodic checkpoint, the run continues without it
+    checkpoint_error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+struct Checkpoint<ProcessT: Process> {
+    seed: u64,
+    rng: Xoshiro256PlusPlus,
+    state: State<ProcessT>,
+    action_counts: Array2<u64>,
+    last_policies: Array2<f32>,
+    node_deltas: Vec<f32>,
+    criterion: serde_json::Value,
+    termination: Option<TerminationReason>,
+    step: usize,
+    last_change_step: usize,
+    fixation: Option<Fixation>,
+    series: Option<TimeSeries>,
+    #[serde(default)]
+    series_changes: usize,
+    burn_in_detector: BurnInDetector,
+    burn_in_steps: Option<usize>,
+    /// Length of the trace file when the checkpoint was written,
+    /// frames behind it are dropped on resume
+    #[serde(default)]
+    trace_offset: Option<u64>,
 }
 
 impl<'a, ProcessT: Process> Simulator<'a, ProcessT> {
+    /// Creates a simulator seeded from `outer_rng`, or from a fixed default seed if it is `None`.
+    /// The seed actually used is available via `seed()` and in `RunReport`.
     pub fn new(
         config: &'a SimulatorConfig,
         outer_rng: Option<&mut ThreadRng>,
         network: &'a Network,
         process: &'a ProcessT,
     ) -> Self {
-        let mut rng = outer_rng
-            .map(|r| SmallRng::from_rng(r).unwrap())
-            .unwrap_or_else(|| {
-                SmallRng::seed_from_u64(0b1110110001110101011000111101) // Doc says that SmallRng should enough 1 in seed
-                                                                        //let mut rng = rand::thread_rng();
-            });
+        let seed = outer_rng.map(|r| r.gen()).unwrap_or(DEFAULT_SEED);
+        Self::with_seed(config, seed, network, process)
+    }
+
+    /// Creates a simulator with an explicit seed; the same seed (and configuration)
+    /// always reproduces the same run. Panics on an invalid configuration, see `try_with_seed`.
+    pub fn with_seed(
+        config: &'a SimulatorConfig,
+        seed: u64,
+        network: &'a Network,
+        process: &'a ProcessT,
+    ) -> Self {
+        Self::try_with_seed(config, seed, network, process).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Like `with_seed`, but returns an error when the configuration is invalid
+    /// (see `SimulatorConfig::validate_for`) or the trace cannot be created.
+    pub fn try_with_seed(
+        config: &'a SimulatorConfig,
+        seed: u64,
+        network: &'a Network,
+        process: &'a ProcessT,
+    ) -> Result<Self, ConfigError> {
+        config.validate_for(network, process)?;
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(seed);
         let state = process.make_initial_state(&mut rng, network);
         assert_eq!(state.node_count(), network.node_count());
 
-        let trace_file = config
-            .trace_path
-            .as_ref()
-            .map(|path| BufWriter::new(File::create(path).unwrap()));
-
-        Simulator {
+        let mut simulator = Simulator {
             network,
-            process,
+            initial_process: process,
+            process: Cow::Borrowed(process),
+            seed,
             rng,
             state,
-            action_counts: Array2::zeros((network.node_count(), ProcessT::ACTIONS)),
-            last_policies: Array2::zeros((network.node_count(), ProcessT::ACTIONS)),
-            converged: false,
+            action_counts: Array2::zeros((network.node_count(), process.actions())),
+            last_policies: Array2::zeros((network.node_count(), process.actions())),
+            node_deltas: Vec::new(),
+            criterion: config.convergence.build(config.termination_threshold),
+            termination: None,
             step: 0,
+            last_change_step: 0,
+            fixation: None,
+            series: (config.series_step > 0).then(TimeSeries::default),
+            series_changes: 0,
+            burn_in_detector: BurnInDetector::default(),
+            burn_in_steps: None,
+            started: None,
+            zealot_actions: Vec::new(),
+            zealots: Vec::new(),
+            signals: Vec::new(),
+            config,
+            observers: Vec::new(),
+            stop_requested: false,
+            checkpoint_error: None,
+        };
+        simulator.init_zealots();
+        for node in simulator.zealots.clone() {
+            let action = simulator.zealot_actions[node].unwrap();
+            simulator.state.set_action(node, action);
+        }
+        if let Some(path) = &config.trace_path {
+            simulator.add_observer(
+                TraceWriter::create(
+                    path,
+                    network.node_count(),
+                    config.trace_format,
+                    config.report_state_step,
+                    config.trace_filter.clone(),
+                )
+                .map_err(|e| match e.kind() {
+                    std::io::ErrorKind::InvalidInput => ConfigError::Invalid(e.to_string()),
+                    _ => ConfigError::Io(path.clone(), e),
+                })?,
+            );
+        }
+        Ok(simulator)
+    }
+
+    /// Continues a run saved by `checkpoint`, including its random stream.
+    /// `config`, `network` and `process` have to be the same as in the original run.
+    /// Trace (if configured) is appended to the existing file.
+    pub fn resume(
+        path: &Path,
+        config: &'a SimulatorConfig,
+        network: &'a Network,
+        process: &'a ProcessT,
+    ) -> std::io::Result<Self> {
+        config
+            .validate_for(network, process)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e))?;
+        let reader = BufReader::new(File::open(path)?);
+        let checkpoint: Checkpoint<ProcessT> = serde_json::from_reader(reader)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        if checkpoint.state.node_count() != network.node_count() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "checkpoint does not match the network",
+            ));
+        }
+
+        let mut criterion = config.convergence.build(config.termination_threshold);
+        criterion.load(checkpoint.criterion).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("checkpoint does not match the convergence criterion: {}", e),
+            )
+        })?;
+
+        let mut simulator = Simulator {
+            network,
+            initial_process: process,
+            process: Cow::Borrowed(process),
+            seed: checkpoint.seed,
+            rng: checkpoint.rng,
+            state: checkpoint.state,
+            action_counts: checkpoint.action_counts,
+            last_policies: checkpoint.last_policies,
+            node_deltas: checkpoint.node_deltas,
+            criterion,
+            termination: checkpoint.termination,
+            step: checkpoint.step,
+            last_change_step: checkpoint.last_change_step,
+            fixation: checkpoint.fixation,
+            series: checkpoint.series,
+            series_changes: checkpoint.series_changes,
+            burn_in_detector: checkpoint.burn_in_detector,
+            burn_in_steps: checkpoint.burn_in_steps,
+            started: None,
+            zealot_actions: Vec::new(),
+            zealots: Vec::new(),
+            signals: Vec::new(),
             config,
-            trace_file,
+            observers: Vec::new(),
+            stop_requested: false,
+            checkpoint_error: None,
+        };
+        simulator.reapply_payoff_interventions();
+        simulator.init_zealots();
+        if let Some(path) = &config.trace_path {
+            simulator.add_observer(TraceWriter::append(
+                path,
+                checkpoint.trace_offset,
+                network.node_count(),
+                config.trace_format,
+                config.report_state_step,
+                config.trace_filter.clone(),
+            )?);
+        }
+        Ok(simulator)
+    }
+
+    /// Replaces the state (and the generator of sequential steps, if given),
+    /// used for re-simulation from a stored state
+    pub(crate) fn restore_state(
+        &mut self,
+        step: usize,
+        state: State<ProcessT>,
+        rng: Option<Xoshiro256PlusPlus>,
+    ) {
+        assert_eq!(state.node_count(), self.network.node_count());
+        self.step = step;
+        self.last_change_step = step;
+        self.state = state;
+        if let Some(rng) = rng {
+            self.rng = rng;
         }
+        self.reapply_payoff_interventions();
     }
 
-    fn write_state_trace(&mut self) {
-        if let Some(file) = &mut self.trace_file {
-            if self.config.report_state_step > 0 && self.step % self.config.report_state_step == 0 {
-                let frame = TraceFrame::<'_, ProcessT>::State(StateTraceFrame {
-                    step: self.step,
-                    states: self.state.node_states(),
-                });
-                writeln!(file, "{}", serde_json::to_string(&frame).unwrap()).unwrap()
+    /// Zealots are selected by a random stream derived from the seed, so a resumed
+    /// or re-simulated run selects the same nodes.
+    fn init_zealots(&mut self) {
+        if self.config.zealots.is_empty() {
+            return;
+        }
+        self.zealot_actions = vec![None; self.network.node_count()];
+        for (index, zealots) in self.config.zealots.iter().enumerate() {
+            let mut rng = node_rng(self.seed, usize::MAX, index);
+            for node in zealots.nodes.resolve(self.network, &mut rng) {
+                self.zealot_actions[node] = Some(zealots.action);
             }
         }
+        self.zealots = (0..self.network.node_count())
+            .filter(|node| self.zealot_actions[*node].is_some())
+            .collect();
     }
 
-    fn write_window_trace(&mut self) {
-        if let Some(file) = &mut self.trace_file {
-            let frame = TraceFrame::<'_, ProcessT>::Window(WindowTraceFrame {
+    fn is_zealot(&self, node: usize) -> bool {
+        self.zealot_actions.get(node).is_some_and(|a| a.is_some())
+    }
+
+    /// Payoff interventions before the current step for a simulator restored at that step
+    fn reapply_payoff_interventions(&mut self) {
+        self.process = Cow::Borrowed(self.initial_process);
+        let mut interventions: Vec<_> = self
+            .config
+            .interventions
+            .iter()
+            .filter(|i| i.step < self.step && i.changes_payoffs())
+            .collect();
+        interventions.sort_by_key(|i| i.step);
+        for intervention in interventions {
+            self.change_payoffs(&intervention.kind);
+        }
+    }
+
+    fn change_payoffs(&mut self, kind: &InterventionKind) {
+        match kind {
+            InterventionKind::SetPayoffs(matrix) => self.process.to_mut().set_payoff_matrix(matrix),
+            InterventionKind::AddPayoffs(delta) => self.process.to_mut().add_payoffs(delta),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Applies interventions scheduled for the current step. Nodes are selected
+    /// by a random stream separate from the simulation stream.
+    fn apply_interventions(&mut self) {
+        let config = self.config;
+        for (index, intervention) in config.interventions.iter().enumerate() {
+            if intervention.step != self.step {
+                continue;
+            }
+            let mut rng = node_rng(self.seed, self.step, usize::MAX - index);
+            let nodes = match &intervention.kind {
+                InterventionKind::SetPayoffs(_) | InterventionKind::AddPayoffs(_) => {
+                    self.change_payoffs(&intervention.kind);
+                    Vec::new()
+                }
+                InterventionKind::ForceActions { nodes, action } => {
+                    // Zealots keep their action
+                    let nodes: Vec<usize> = nodes
+                        .resolve(self.network, &mut rng)
+                        .into_iter()
+                        .filter(|node| !self.is_zealot(*node))
+                        .collect();
+                    for node in &nodes {
+                        if self.state.set_action(*node, *action) {
+                            self.last_change_step = self.step;
+                        }
+                    }
+                    nodes
+                }
+                InterventionKind::PerturbStates { nodes, strength } => {
+                    let nodes = nodes.resolve(self.network, &mut rng);
+                    if !nodes.is_empty() {
+                        self.last_change_step = self.step;
+                    }
+                    for node in &nodes {
+                        self.process.perturb_node_state(
+                            &mut rng,
+                            self.state.node_state_mut(*node),
+                            *strength,
+                        );
+                    }
+                    nodes
+                }
+            };
+            let observation = Observation {
+                seed: self.seed,
                 step: self.step,
-                actions: self.action_counts.ncols(),
-                counts: self.action_counts.as_slice().unwrap(),
-            });
-            writeln!(file, "{}", serde_json::to_string(&frame).unwrap()).unwrap()
+                state: &self.state,
+                action_counts: &self.action_counts,
+                rng: &self.rng,
+            };
+            for observer in &mut self.observers {
+                observer.on_intervention(&observation, intervention, &nodes);
+            }
+        }
+    }
+
+    /// Registers an observer that is notified about steps, windows and termination.
+    /// A `TraceWriter` is registered automatically when `trace_path` is configured.
+    pub fn add_observer(&mut self, observer: impl Observer<ProcessT> + 'a) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Saves the complete simulator state; the file is replaced atomically
+    pub fn checkpoint(&mut self, path: &Path) -> std::io::Result<()> {
+        let observation = Observation {
+            seed: self.seed,
+            step: self.step,
+            state: &self.state,
+            action_counts: &self.action_counts,
+            rng: &self.rng,
+        };
+        for observer in &mut self.observers {
+            observer.on_checkpoint(&observation);
+        }
+        let trace_offset = match &self.config.trace_path {
+            Some(trace_path) => Some(std::fs::metadata(trace_path)?.len()),
+            None => None,
+        };
+        let checkpoint = Checkpoint::<ProcessT> {
+            seed: self.seed,
+            rng: self.rng.clone(),
+            state: self.state.clone(),
+            action_counts: self.action_counts.clone(),
+            last_policies: self.last_policies.clone(),
+            node_deltas: self.node_deltas.clone(),
+            criterion: self.criterion.save(),
+            termination: self.termination,
+            step: self.step,
+            last_change_step: self.last_change_step,
+            fixation: self.fixation.clone(),
+            series: self.series.clone(),
+            series_changes: self.series_changes,
+            burn_in_detector: self.burn_in_detector.clone(),
+            burn_in_steps: self.burn_in_steps,
+            trace_offset,
+        };
+        let tmp_path = path.with_extension("tmp");
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        serde_json::to_writer(&mut writer, &checkpoint)?;
+        writer.flush()?;
+        drop(writer);
+        std::fs::rename(tmp_path, path)
+    }
+
+    fn periodic_checkpoint(&mut self) {
+        if let Some(path) = &self.config.checkpoint_path {
+            if self.config.checkpoint_step > 0
+                && self.step.is_multiple_of(self.config.checkpoint_step)
+            {
+                if let Err(error) = self.checkpoint(path) {
+                    self.checkpoint_error = Some(format!("{}: {}", path.display(), error));
+                }
+            }
+        }
+    }
+
+    fn notify_step(&mut self) {
+        let observation = Observation {
+            seed: self.seed,
+            step: self.step,
+            state: &self.state,
+            action_counts: &self.action_counts,
+            rng: &self.rng,
+        };
+        for observer in &mut self.observers {
+            if observer.on_step(&observation) == ObserverAction::Stop {
+                self.stop_requested = true;
+            }
+        }
+    }
+
+    fn notify_window(&mut self) {
+        let observation = Observation {
+            seed: self.seed,
+            step: self.step,
+            state: &self.state,
+            action_counts: &self.action_counts,
+            rng: &self.rng,
+        };
+        for observer in &mut self.observers {
+            if observer.on_window(&observation) == ObserverAction::Stop {
+                self.stop_requested = true;
+            }
+        }
+    }
+
+    fn notify_termination(&mut self) {
+        let report = self.report();
+        let observation = Observation {
+            seed: self.seed,
+            step: self.step,
+            state: &self.state,
+            action_counts: &self.action_counts,
+            rng: &self.rng,
+        };
+        for observer in &mut self.observers {
+            observer.on_termination(&observation, &report);
         }
     }
 
     pub(crate) fn step(&mut self) {
+        if !self.config.interventions.is_empty() {
+            self.apply_interventions();
+        }
+        let (changed, state_changed) = if self.config.parallel {
+            self.parallel_step()
+        } else {
+            self.sequential_step()
+        };
+        if changed > 0 || state_changed {
+            self.last_change_step = self.step;
+        }
+        self.record_series(changed);
+        self.notify_step();
+    }
+
+    pub(crate) fn action_fractions(&self) -> Vec<f32> {
+        let mut fractions = vec![0.0f32; self.process.actions()];
+        let actions = self.state.last_actions();
+        for action in actions {
+            fractions[*action] += 1.0;
+        }
+        fractions
+            .iter_mut()
+            .for_each(|f| *f /= actions.len() as f32);
+        fractions
+    }
+
+    fn record_series(&mut self, changed: usize) {
+        if self.series.is_none() {
+            return;
+        }
+        self.series_changes += changed;
+        if self.step.is_multiple_of(self.config.series_step) {
+            let fractions = self.action_fractions();
+            if let Some(series) = &mut self.series {
+                series.steps.push(self.step);
+                series.action_fractions.push(fractions);
+                series.changed_nodes.push(self.series_changes);
+            }
+            self.series_changes = 0;
+        }
+    }
+
+    /// Returns the number of nodes that changed their action and whether any node state changed
+    fn sequential_step(&mut self) -> (usize, bool) {
         self.step += 1;
+        let track_states = self.config.absorbing_steps > 0;
+        let mut state_changed = false;
         let graph = self.network.graph();
-        let node_states = self.state.node_states();
-        let last_actions = self.state.last_actions();
-        let mut idx = 0u32;
-        let (new_node_states, new_actions): (Vec<_>, Vec<_>) = node_states
+        let (node_states, last_actions, next_states, next_actions) = self.state.buffers();
+        self.signals.clear();
+        self.signals.extend(
+            node_states
+                .iter()
+                .zip(last_actions)
+                .map(|(node_state, action)| self.process.signal(node_state, *action)),
+        );
+        let signals = &self.signals;
+        let mut changed = 0;
+        for (idx, ((node_state, last_action), (next_state, next_action))) in node_states
             .iter()
             .zip(last_actions)
-            .map(|(node_state, last_action)| {
-                let neighbors = graph
-                    .neighbors(idx.into())
-                    .map(|other| last_actions[other.index()]);
-                let (new_state, action) =
-                    self.process
-                        .node_step(&mut self.rng, node_state, *last_action, neighbors);
-                self.action_counts[(idx as usize, action)] += 1;
-                idx += 1;
-                (new_state, action)
-            })
-            .unzip();
-        self.state = State::new(new_node_states, new_actions);
-        self.write_state_trace();
+            .zip(next_states.iter_mut().zip(next_actions.iter_mut()))
+            .enumerate()
+        {
+            let neighbors = graph
+                .neighbors((idx as u32).into())
+                .map(|other| signals[other.index()].clone());
+            let context = NodeContext::new(self.network, idx, self.step);
+            let (new_state, mut action) = self.process.node_step(
+                &mut self.rng,
+                &context,
+                node_state,
+                *last_action,
+                neighbors,
+            );
+            if let Some(Some(zealot_action)) = self.zealot_actions.get(idx) {
+                action = *zealot_action;
+            }
+            self.action_counts[(idx, action)] += 1;
+            if action != *last_action {
+                changed += 1;
+            }
+            if track_states && !state_changed {
+                state_changed = self.process.node_state_changed(node_state, &new_state);
+            }
+            *next_state = new_state;
+            *next_action = action;
+        }
+        self.state.swap();
+        (changed, state_changed)
+    }
+
+    /// Returns the number of nodes that changed their action and whether any node state changed
+    fn parallel_step(&mut self) -> (usize, bool) {
+        self.step += 1;
+        let graph = self.network.graph();
+        let process = &*self.process;
+        let network = self.network;
+        let seed = self.seed;
+        let step = self.step;
+        let (node_states, last_actions, next_states, next_actions) = self.state.buffers();
+        self.signals.clear();
+        self.signals.par_extend(
+            node_states
+                .par_iter()
+                .zip(last_actions.par_iter())
+                .map(|(node_state, action)| process.signal(node_state, *action)),
+        );
+        let signals = &self.signals;
+        node_states
+            .par_iter()
+            .zip(last_actions.par_iter())
+            .zip(next_states.par_iter_mut().zip(next_actions.par_iter_mut()))
+            .enumerate()
+            .for_each(
+                |(idx, ((node_state, last_action), (next_state, next_action)))| {
+                    let mut rng = node_rng(seed, step, idx);
+                    let neighbors = graph
+                        .neighbors((idx as u32).into())
+                        .map(|other| signals[other.index()].clone());
+                    let context = NodeContext::new(network, idx, step);
+                    (*next_state, *next_action) =
+                        process.node_step(&mut rng, &context, node_state, *last_action, neighbors);
+                },
+            );
+        for node in &self.zealots {
+            next_actions[*node] = self.zealot_actions[*node].unwrap();
+        }
+        let mut changed = 0;
+        for (idx, (action, last_action)) in next_actions.iter().zip(last_actions).enumerate() {
+            self.action_counts[(idx, *action)] += 1;
+            if action != last_action {
+                changed += 1;
+            }
+        }
+        let state_changed = self.config.absorbing_steps > 0
+            && node_states
+                .par_iter()
+                .zip(next_states.par_iter())
+                .any(|(previous, next)| process.node_state_changed(previous, next));
+        self.state.swap();
+        (changed, state_changed)
     }
 
     pub fn report(&self) -> RunReport {
         RunReport {
+            seed: self.seed,
             steps: self.step,
-            converged: self.converged,
-            //action_counts: self.action_counts,
+            termination: self.termination,
+            burn_in_steps: self.burn_in_steps,
             avg_policy: self.last_policies.clone(),
+            zealots: self.zealots.clone(),
+            groups: self.group_policies(),
+            fixation: self.fixation.clone(),
+            series: self.series.clone(),
+            checkpoint_error: self.checkpoint_error.clone(),
+            details: self.config.report_details.then(|| RunDetails {
+                actions: self.state.last_actions().to_vec(),
+                node_states: serde_json::to_value(self.state.node_states()).unwrap(),
+                action_counts: self.action_counts.clone(),
+                node_deltas: self.node_deltas.clone(),
+            }),
+            configuration: RunConfiguration {
+                process: self.initial_process.configuration(),
+                network: serde_json::to_value(self.network.description()).unwrap(),
+                simulator: serde_json::to_value(self.config).unwrap(),
+            },
+        }
+    }
+
+    fn group_policies(&self) -> Vec<GroupPolicy> {
+        let mut groups: Vec<GroupPolicy> = self
+            .process
+            .groups()
+            .into_iter()
+            .map(|name| GroupPolicy {
+                name,
+                nodes: 0,
+                policy: vec![0.0; self.process.actions()],
+            })
+            .collect();
+        if groups.is_empty() {
+            return groups;
+        }
+        for (node, node_state) in self.state.node_states().iter().enumerate() {
+            if self.is_zealot(node) {
+                continue;
+            }
+            let group = &mut groups[self.process.node_group(node_state)];
+            group.nodes += 1;
+            for (p, v) in group.policy.iter_mut().zip(self.last_policies.row(node)) {
+                *p += v;
+            }
         }
+        for group in groups.iter_mut().filter(|g| g.nodes > 0) {
+            group
+                .policy
+                .iter_mut()
+                .for_each(|p| *p /= group.nodes as f32);
+        }
+        groups
     }
 
     pub fn reset_counts(&mut self) {
@@ -196,34 +1029,503 @@ impl<'a, ProcessT: Process> Simulator<'a, ProcessT> {
         self.action_counts.mapv(|x| x as f32) / sums.insert_axis(Axis(1))
     }
 
-    pub fn run(&mut self) -> bool {
-        self.write_state_trace();
-        for _i in 0..self.config.bootstrap_steps {
-            self.step();
+    /// Runs bootstrap and then measurement windows until termination.
+    /// The loop is driven only by the step counter, so a resumed simulator continues where it stopped.
+    pub fn run(&mut self) -> TerminationReason {
+        self.run_until(usize::MAX).unwrap()
+    }
+
+    /// Like `run` but pauses when `step` is reached and returns `None`;
+    /// a later call continues the run. Observer stop requests made before the call are ignored.
+    pub fn run_until(&mut self, step: usize) -> Option<TerminationReason> {
+        if self.termination.is_some() {
+            return self.termination;
         }
-        self.write_window_trace();
-        self.reset_counts();
-        for _j in 0..self.config.max_windows {
-            for _i in 0..self.config.window_steps {
-                self.step();
+        self.stop_requested = false;
+        let reason = self.run_loop(step)?;
+        self.finish(reason);
+        Some(reason)
+    }
+
+    /// Terminates the run for a reason decided outside of the simulator
+    pub(crate) fn finish(&mut self, reason: TerminationReason) {
+        self.termination = Some(reason);
+        self.notify_termination();
+    }
+
+    fn run_loop(&mut self, until_step: usize) -> Option<TerminationReason> {
+        let max_wall_time = self.config.max_wall_time.map(Duration::from_secs_f64);
+        let window_steps = self.config.window_steps;
+        if self.started.is_none() {
+            self.started = Some(Instant::now());
+            if self.step == 0 {
+                self.record_series(0);
+                self.notify_step();
+                if self.is_burn_in_finished() {
+                    self.finish_bootstrap();
+                }
+            }
+        }
+        let started = self.started.unwrap();
+        loop {
+            if let Some(burn_in_steps) = self.burn_in_steps {
+                if self.step >= burn_in_steps + self.config.max_windows * window_steps {
+                    return Some(TerminationReason::MaxWindows);
+                }
+            }
+            if self.step >= until_step {
+                return None;
+            }
+            self.step();
+            if self.check_absorption() {
+                return Some(TerminationReason::Absorbed);
             }
-            self.write_window_trace();
-            if self.check_termination() {
-                self.converged = true;
-                return true;
+            match self.burn_in_steps {
+                None => {
+                    if self.is_burn_in_finished() {
+                        self.finish_bootstrap();
+                    }
+                }
+                Some(burn_in_steps) => {
+                    if (self.step - burn_in_steps).is_multiple_of(window_steps) {
+                        self.notify_window();
+                        if self.check_termination() {
+                            return Some(TerminationReason::Converged);
+                        }
+                    }
+                }
+            }
+            if self.stop_requested {
+                return Some(TerminationReason::Stopped);
+            }
+            self.periodic_checkpoint();
+            if self.config.max_steps > 0 && self.step >= self.config.max_steps {
+                return Some(TerminationReason::StepBudget);
+            }
+            if max_wall_time.is_some_and(|limit| started.elapsed() >= limit) {
+                return Some(TerminationReason::TimeBudget);
             }
         }
-        false
+    }
+
+    /// Called after every burn-in step; `bootstrap_steps` always ends burn-in
+    fn is_burn_in_finished(&mut self) -> bool {
+        if self.step >= self.config.bootstrap_steps {
+            return true;
+        }
+        match self.config.burn_in {
+            BurnIn::Fixed => false,
+            BurnIn::Geweke {
+                sample_step,
+                samples,
+                z_threshold,
+            } => {
+                self.step.is_multiple_of(sample_step)
+                    && self
+                        .burn_in_detector
+                        .push(self.action_fractions(), samples, z_threshold)
+            }
+        }
+    }
+
+    fn finish_bootstrap(&mut self) {
+        self.burn_in_steps = Some(self.step);
+        self.burn_in_detector = BurnInDetector::default();
+        self.notify_window();
+        self.reset_counts();
+    }
+
+    /// Frozen configuration is treated as converged, the average policy is then
+    /// the final action profile as it never changes again.
+    fn check_absorption(&mut self) -> bool {
+        if self.config.absorbing_steps == 0
+            || self.step - self.last_change_step < self.config.absorbing_steps
+        {
+            return false;
+        }
+        let actions = self.state.last_actions().to_vec();
+        self.last_policies.fill(0.0);
+        for (idx, action) in actions.iter().enumerate() {
+            self.last_policies[(idx, *action)] = 1.0;
+        }
+        self.fixation = Some(Fixation {
+            step: self.last_change_step,
+            actions,
+        });
+        self.notify_window();
+        true
     }
 
     fn check_termination(&mut self) -> bool {
         let policies = self.compute_policies();
-        let v = max_of_array(&policies - &self.last_policies);
+        let check = if self.zealots.is_empty() {
+            self.criterion.check(&self.last_policies, &policies)
+        } else {
+            let free: Vec<usize> = (0..self.network.node_count())
+                .filter(|node| self.zealot_actions[*node].is_none())
+                .collect();
+            self.criterion.check(
+                &self.last_policies.select(Axis(0), &free),
+                &policies.select(Axis(0), &free),
+            )
+        };
+        self.node_deltas = policies
+            .rows()
+            .into_iter()
+            .zip(self.last_policies.rows())
+            .map(|(p, l)| {
+                p.iter()
+                    .zip(l)
+                    .fold(0.0f32, |m, (a, b)| m.max((a - b).abs()))
+            })
+            .collect();
         self.last_policies = policies;
-        v < self.config.termination_threshold
+        if let Some(series) = &mut self.series {
+            series.window_steps.push(self.step);
+            series.window_deltas.push(check.delta);
+        }
+        check.converged
+    }
+
+    pub fn current_step(&self) -> usize {
+        self.step
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
     }
 
     pub fn state(&mut self) -> &State<ProcessT> {
         &self.state
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::games::chooser::BestResponseEpsilonError;
+    use crate::games::counting::ActionCountingProcess;
+    use crate::games::game::{InitialAction, MatrixGame};
+    use crate::process::burnin::BurnIn;
+    use crate::process::convergence::Convergence;
+    use crate::process::intervention::{Intervention, InterventionKind, NodeSelection};
+    use crate::process::network::Network;
+    use crate::process::observer::{Observation, Observer, ObserverAction};
+    use crate::process::process::Process;
+    use crate::process::report::{RunReport, TerminationReason};
+    use crate::process::simulator::{Simulator, SimulatorConfig};
+    use crate::process::testing::{config_error, coordination_process, windows_config};
+    use crate::process::trace::TraceFilter;
+    use std::time::Duration;
+
+    #[test]
+    fn test_parallel_step_independent_of_threads() {
+        let game = coordination_process();
+        let network = Network::grid(10, 10);
+        let mut config = SimulatorConfig::new();
+        config.set_parallel(true);
+
+        let run = |threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let mut simulator = Simulator::new(&config, None, &network, &game);
+                for _ in 0..100 {
+                    simulator.step();
+                }
+                simulator.state().last_actions().to_vec()
+            })
+        };
+        let actions = run(1);
+        assert_eq!(actions.len(), 100);
+        assert_eq!(actions, run(4));
+        assert_eq!(actions, run(7));
+    }
+
+    #[test]
+    fn test_seed_reproducibility() {
+        let game = coordination_process();
+        let network = Network::grid(4, 4);
+        let config = windows_config(100, 3);
+
+        let run = |seed| {
+            let mut simulator = Simulator::with_seed(&config, seed, &network, &game);
+            simulator.run();
+            simulator.report()
+        };
+        let report = run(1234);
+        assert_eq!(report.seed, 1234);
+        assert_eq!(report.avg_policy, run(1234).avg_policy);
+        assert_ne!(report.avg_policy, run(4321).avg_policy);
+    }
+
+    #[test]
+    fn test_budgets() {
+        let game = coordination_process();
+        let network = Network::grid(3, 3);
+        let mut config = windows_config(10, 4);
+
+        let run = |config: &SimulatorConfig| {
+            let mut simulator = Simulator::new(config, None, &network, &game);
+            assert_eq!(simulator.report().termination, None);
+            let reason = simulator.run();
+            (reason, simulator.report())
+        };
+        let (reason, report) = run(&config);
+        assert_eq!(reason, TerminationReason::MaxWindows);
+        assert_eq!(report.steps, 50);
+
+        config.set_max_steps(25);
+        let (reason, report) = run(&config);
+        assert_eq!(reason, TerminationReason::StepBudget);
+        assert_eq!(report.steps, 25);
+        assert_eq!(report.termination, Some(TerminationReason::StepBudget));
+        assert!(!report.converged());
+
+        config.set_max_steps(0);
+        config.set_max_wall_time(Duration::from_nanos(1));
+        let (reason, report) = run(&config);
+        assert_eq!(reason, TerminationReason::TimeBudget);
+        assert_eq!(report.steps, 1);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let game = coordination_process();
+        let network = Network::grid(3, 3);
+        let mut config = SimulatorConfig::new();
+        config.set_convergence(Convergence::MovingVariance { windows: 1 });
+        assert!(config_error(&config, &network, &game).contains("MovingVariance.windows"));
+
+        let mut config = SimulatorConfig::new();
+        config.set_burn_in(BurnIn::Geweke {
+            sample_step: 10,
+            samples: 3,
+            z_threshold: 1.0,
+        });
+        assert!(config_error(&config, &network, &game).contains("burn_in.Geweke.samples"));
+
+        let mut config = SimulatorConfig::new();
+        let path = std::env::temp_dir().join("pon-test-invalid-trace.json");
+        config.set_trace_path(&path);
+        config.set_trace_filter(TraceFilter {
+            nodes: Some(vec![2, 9]),
+            ..Default::default()
+        });
+        assert!(config_error(&config, &network, &game).contains("traced node 9"));
+        assert!(!path.exists());
+
+        let intervention_error = |kind: InterventionKind| {
+            let mut config = SimulatorConfig::new();
+            config.add_intervention(Intervention::new(3, kind));
+            config_error(&config, &network, &game)
+        };
+        assert!(intervention_error(InterventionKind::ForceActions {
+            nodes: NodeSelection::TopDegree(1),
+            action: 2,
+        })
+        .contains("action 2 is not one of 2 actions"));
+        assert!(intervention_error(InterventionKind::PerturbStates {
+            nodes: NodeSelection::Nodes(vec![0, 9]),
+            strength: 0.5,
+        })
+        .contains("node 9 is not in the network"));
+        assert!(
+            intervention_error(InterventionKind::SetPayoffs(vec![vec![0.0; 3]; 3]))
+                .contains("payoff matrix has to be 2x2")
+        );
+
+        let mut config = SimulatorConfig::new();
+        config.add_zealots(NodeSelection::TopDegree(2), 2);
+        assert!(config_error(&config, &network, &game).contains("zealots: action 2"));
+    }
+
+    #[test]
+    fn test_zealots() {
+        let game = ActionCountingProcess::new(
+            MatrixGame::new([[1.0, 0.5], [0.5, 0.0]], InitialAction::Const(0)),
+            BestResponseEpsilonError::new(0.0),
+        );
+        let network = Network::line(5);
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(10);
+        config.set_window_steps(10);
+        config.set_max_windows(5);
+        config.add_zealots(NodeSelection::Nodes(vec![3, 0]), 1);
+        config.add_intervention(Intervention::new(
+            12,
+            InterventionKind::ForceActions {
+                nodes: NodeSelection::Nodes(vec![0, 1, 2]),
+                action: 0,
+            },
+        ));
+
+        for parallel in [false, true] {
+            config.set_parallel(parallel);
+            let mut observer = InterventionObserver::default();
+            let mut simulator = Simulator::with_seed(&config, 1, &network, &game);
+            simulator.add_observer(&mut observer);
+            assert_eq!(simulator.state().last_actions(), &[1, 0, 0, 1, 0]);
+            assert_eq!(simulator.run(), TerminationReason::Converged);
+            let report = simulator.report();
+            assert_eq!(report.zealots, vec![0, 3]);
+            assert_eq!(report.avg_policy.row(0).to_vec(), vec![0.0, 1.0]);
+            assert_eq!(report.avg_policy.row(3).to_vec(), vec![0.0, 1.0]);
+            assert_eq!(report.population_policy(), vec![1.0, 0.0]);
+            drop(simulator);
+            // Zealot 0 is not forced
+            assert_eq!(observer.nodes, vec![vec![1, 2]]);
+        }
+
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(10);
+        config.add_zealots(NodeSelection::RandomFraction(1.0), 0);
+        let mut simulator = Simulator::with_seed(&config, 1, &network, &game);
+        simulator.run();
+        assert!(simulator.report().population_policy().is_empty());
+    }
+
+    #[derive(Default)]
+    struct InterventionObserver {
+        nodes: Vec<Vec<usize>>,
+    }
+
+    impl<ProcessT: Process> Observer<ProcessT> for InterventionObserver {
+        fn on_intervention(
+            &mut self,
+            _observation: &Observation<ProcessT>,
+            _intervention: &Intervention,
+            nodes: &[usize],
+        ) {
+            self.nodes.push(nodes.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_absorbing_state() {
+        let game = ActionCountingProcess::new(
+            MatrixGame::new([[1.0, 0.5], [0.5, 0.0]], InitialAction::Const(1)),
+            BestResponseEpsilonError::new(0.0),
+        );
+        let network = Network::line(3);
+        let mut config = SimulatorConfig::new();
+        config.set_absorbing_steps(10);
+        let mut simulator = Simulator::new(&config, None, &network, &game);
+        assert_eq!(simulator.run(), TerminationReason::Absorbed);
+
+        let report = simulator.report();
+        assert_eq!(report.steps, 11);
+        let fixation = report.fixation.unwrap();
+        assert_eq!(fixation.step, 1);
+        assert_eq!(fixation.actions, vec![0, 0, 0]);
+        for i in 0..3 {
+            assert_eq!(report.avg_policy[(i, 0)], 1.0);
+            assert_eq!(report.avg_policy[(i, 1)], 0.0);
+        }
+    }
+
+    #[test]
+    fn test_time_series() {
+        let game = ActionCountingProcess::new(
+            MatrixGame::new([[1.0, 0.5], [0.5, 0.0]], InitialAction::Const(1)),
+            BestResponseEpsilonError::new(0.0),
+        );
+        let network = Network::line(3);
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(10);
+        config.set_window_steps(5);
+        config.set_max_windows(2);
+        config.set_series_step(5);
+        let mut simulator = Simulator::new(&config, None, &network, &game);
+        simulator.run();
+
+        let series = simulator.report().series.unwrap();
+        assert_eq!(series.steps, vec![0, 5, 10, 15, 20]);
+        assert_eq!(series.action_fractions[0], vec![0.0, 1.0]);
+        assert_eq!(series.action_fractions[4], vec![1.0, 0.0]);
+        // Every node switches to the dominant action at step 1
+        assert_eq!(series.changed_nodes, vec![0, 3, 0, 0, 0]);
+        assert_eq!(series.window_steps, vec![15, 20]);
+        assert_eq!(series.window_deltas.len(), 2);
+        assert_eq!(series.window_deltas[1], 0.0);
+    }
+
+    #[derive(Default)]
+    struct StopObserver {
+        steps: Vec<usize>,
+        windows: usize,
+        terminated: bool,
+    }
+
+    impl<ProcessT: Process> Observer<ProcessT> for StopObserver {
+        fn on_step(&mut self, observation: &Observation<ProcessT>) -> ObserverAction {
+            assert_eq!(observation.state.node_count(), 4);
+            self.steps.push(observation.step);
+            if observation.step == 50 {
+                ObserverAction::Stop
+            } else {
+                ObserverAction::Continue
+            }
+        }
+
+        fn on_window(&mut self, observation: &Observation<ProcessT>) -> ObserverAction {
+            assert_eq!(observation.action_counts.sum(), 4 * 20);
+            self.windows += 1;
+            ObserverAction::Continue
+        }
+
+        fn on_termination(&mut self, _observation: &Observation<ProcessT>, report: &RunReport) {
+            assert_eq!(report.steps, 50);
+            self.terminated = true;
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let game = coordination_process();
+        let network = Network::line(4);
+        let mut config = SimulatorConfig::new();
+        config.set_bootstrap_steps(20);
+        config.set_window_steps(20);
+
+        let mut observer = StopObserver::default();
+        let mut simulator = Simulator::new(&config, None, &network, &game);
+        simulator.add_observer(&mut observer);
+        assert_eq!(simulator.run(), TerminationReason::Stopped);
+        assert_eq!(simulator.report().steps, 50);
+        drop(simulator);
+
+        assert_eq!(observer.steps, (0..=50).collect::<Vec<_>>());
+        assert_eq!(observer.windows, 2);
+        assert!(observer.terminated);
+    }
+
+    struct StopAt(usize);
+
+    impl<ProcessT: Process> Observer<ProcessT> for StopAt {
+        fn on_step(&mut self, observation: &Observation<ProcessT>) -> ObserverAction {
+            if observation.step == self.0 {
+                ObserverAction::Stop
+            } else {
+                ObserverAction::Continue
+            }
+        }
+    }
+
+    #[test]
+    fn test_stop_request_is_reset() {
+        let game = coordination_process();
+        let network = Network::line(4);
+        let config = windows_config(20, 3);
+
+        let mut simulator = Simulator::new(&config, None, &network, &game);
+        simulator.add_observer(StopAt(5));
+        // Stop requested while stepping outside of `run` does not end the following run
+        while simulator.current_step() < 10 {
+            simulator.step();
+        }
+        assert_eq!(simulator.run(), TerminationReason::MaxWindows);
+        assert_eq!(simulator.report().steps, 80);
+    }
+}
@@ -1,17 +1,37 @@
 use crate::games::game::ActionId;
 use crate::process::network::Network;
 use crate::process::process::Process;
+use serde::{Deserialize, Serialize};
 
+/// Node states and actions of the current step. A simulation step reads the current
+/// buffers and writes into back buffers (see `buffers`) that are then swapped,
+/// so no memory is allocated per step.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
 pub struct State<ProcessT: Process> {
     node_states: Vec<ProcessT::NodeStateT>,
     last_actions: Vec<ActionId>,
+    #[serde(skip)]
+    next_node_states: Vec<ProcessT::NodeStateT>,
+    #[serde(skip)]
+    next_actions: Vec<ActionId>,
 }
 
+pub(crate) type Buffers<'a, NodeStateT> = (
+    &'a [NodeStateT],
+    &'a [ActionId],
+    &'a mut [NodeStateT],
+    &'a mut [ActionId],
+);
+
 impl<ProcessT: Process> State<ProcessT> {
     pub fn new(node_states: Vec<ProcessT::NodeStateT>, last_actions: Vec<ActionId>) -> Self {
+        assert_eq!(node_states.len(), last_actions.len());
         Self {
             node_states,
             last_actions,
+            next_node_states: Vec::new(),
+            next_actions: Vec::new(),
         }
     }
 
@@ -34,4 +54,42 @@ impl<ProcessT: Process> State<ProcessT> {
     pub fn node_count(&self) -> usize {
         self.node_states.len()
     }
+
+    /// Returns true if the action of the node was changed
+    pub(crate) fn set_action(&mut self, node: usize, action: ActionId) -> bool {
+        let changed = self.last_actions[node] != action;
+        self.last_actions[node] = action;
+        changed
+    }
+
+    pub(crate) fn node_state_mut(&mut self, node: usize) -> &mut ProcessT::NodeStateT {
+        &mut self.node_states[node]
+    }
+
+    /// Current node states and actions together with back buffers for the next step.
+    /// Back buffers are allocated on the first call only.
+    pub(crate) fn buffers(&mut self) -> Buffers<'_, ProcessT::NodeStateT> {
+        if self.next_node_states.len() != self.node_states.len() {
+            self.next_node_states = self.node_states.clone();
+            self.next_actions = self.last_actions.clone();
+        }
+        (
+            &self.node_states,
+            &self.last_actions,
+            &mut self.next_node_states,
+            &mut self.next_actions,
+        )
+    }
+
+    /// Makes back buffers filled by a step the current state
+    pub(crate) fn swap(&mut self) {
+        std::mem::swap(&mut self.node_states, &mut self.next_node_states);
+        std::mem::swap(&mut self.last_actions, &mut self.next_actions);
+    }
+}
+
+impl<ProcessT: Process> Clone for State<ProcessT> {
+    fn clone(&self) -> Self {
+        Self::new(self.node_states.clone(), self.last_actions.clone())
+    }
 }
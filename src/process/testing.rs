@@ -0,0 +1,36 @@
+use crate::games::chooser::SoftmaxSample;
+use crate::games::counting::ActionCountingProcess;
+use crate::games::game::{InitialAction, MatrixGame};
+use crate::process::network::Network;
+use crate::process::process::Process;
+use crate::process::simulator::{Simulator, SimulatorConfig};
+
+/// Two-action coordination game with uniformly random initial actions
+pub(crate) fn coordination_process() -> ActionCountingProcess<SoftmaxSample, MatrixGame<2>> {
+    ActionCountingProcess::new(
+        MatrixGame::new([[1.0, 0.0], [0.0, 1.0]], InitialAction::Uniform),
+        SoftmaxSample,
+    )
+}
+
+/// Runs `steps` bootstrap steps and then exactly `windows` windows of `steps` steps
+pub(crate) fn windows_config(steps: usize, windows: usize) -> SimulatorConfig {
+    let mut config = SimulatorConfig::new();
+    config.set_bootstrap_steps(steps);
+    config.set_window_steps(steps);
+    config.set_max_windows(windows);
+    config.set_termination_threshold(0.0);
+    config
+}
+
+/// Message of the error `config` is rejected with
+pub(crate) fn config_error<P: Process>(
+    config: &SimulatorConfig,
+    network: &Network,
+    process: &P,
+) -> String {
+    match Simulator::try_with_seed(config, 0, network, process) {
+        Ok(_) => panic!("invalid configuration was accepted"),
+        Err(error) => error.to_string(),
+    }
+}
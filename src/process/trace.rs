@@ -0,0 +1,665 @@
+use crate::games::game::ActionId;
+use crate::process::intervention::Intervention;
+use crate::process::observer::{Observation, Observer, ObserverAction};
+use crate::process::process::Process;
+use crate::process::report::RunReport;
+use flate2::read::MultiGzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use ndarray::Axis;
+use rand_xoshiro::Xoshiro256PlusPlus;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+const BINARY_MAGIC: &[u8; 4] = b"PONT";
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+const STATE_FRAME: u8 = 0;
+const WINDOW_FRAME: u8 = 1;
+const START_FRAME: u8 = 2;
+const DELTA_FRAME: u8 = 3;
+const SUMMARY_FRAME: u8 = 4;
+const INTERVENTION_FRAME: u8 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TraceFormat {
+    /// One JSON `TraceFrame` per line
+    #[default]
+    Json,
+    /// Stream of length-prefixed bincode frames
+    Binary,
+    /// Binary stream compressed by gzip; every checkpoint starts a new gzip member
+    BinaryGzip,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TraceMode {
+    /// Actions and node states of traced nodes
+    #[default]
+    Full,
+    /// Only traced nodes that changed their action since the previous frame
+    Delta,
+    /// Only aggregated action counts of traced nodes
+    Summary,
+}
+
+/// Restricts what is written into a trace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TraceFilter {
+    /// Trace only the given nodes (all nodes if `None`)
+    pub nodes: Option<Vec<usize>>,
+    /// Frames at logarithmically spaced steps, given number of frames per decade,
+    /// instead of every `report_state_step` steps
+    pub log_steps_per_decade: Option<usize>,
+    pub mode: TraceMode,
+}
+
+impl TraceFilter {
+    /// Fails if a traced node is not in a network of `node_count` nodes
+    fn check_nodes(&self, node_count: usize) -> std::io::Result<()> {
+        match self.nodes.iter().flatten().find(|n| **n >= node_count) {
+            Some(node) => Err(std::io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "traced node {} is not in the network of {} nodes",
+                    node, node_count
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn is_traced_step(&self, step: usize, report_state_step: usize) -> bool {
+        match self.log_steps_per_decade {
+            Some(per_decade) => {
+                let bucket = |s: usize| (per_decade as f64 * (s as f64).log10()).floor();
+                step <= 1 || bucket(step) > bucket(step - 1)
+            }
+            None => report_state_step > 0 && step.is_multiple_of(report_state_step),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "evt")]
+pub enum TraceFrame<'a, ProcessT: Process> {
+    Start(StartTraceFrame),
+    State(StateTraceFrame<'a, ProcessT::NodeStateT>),
+    Delta(DeltaTraceFrame),
+    Summary(SummaryTraceFrame),
+    Window(WindowTraceFrame<'a>),
+    Intervention(InterventionTraceFrame),
+}
+
+/// Written at the beginning of a run, the seed allows to re-simulate the run.
+/// When only a subset of nodes is traced, `traced` contains their indices
+/// and all values in other frames refer to these nodes in this order.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StartTraceFrame {
+    pub seed: u64,
+    pub nodes: usize,
+    pub traced: Option<Vec<usize>>,
+}
+
+#[derive(Serialize)]
+pub struct StateTraceFrame<'a, NodeStateT: Serialize + Clone> {
+    step: usize,
+    actions: Cow<'a, [ActionId]>,
+    states: Cow<'a, [NodeStateT]>,
+    rng: Option<&'a Xoshiro256PlusPlus>,
+}
+
+/// Nodes (global indices) that changed action since the previous state or delta frame
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DeltaTraceFrame {
+    pub step: usize,
+    pub nodes: Vec<usize>,
+    pub actions: Vec<ActionId>,
+}
+
+/// Number of traced nodes playing each action
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SummaryTraceFrame {
+    pub step: usize,
+    pub counts: Vec<u64>,
+}
+
+#[derive(Serialize)]
+pub struct WindowTraceFrame<'a> {
+    step: usize,
+    actions: usize,
+    counts: Cow<'a, [u64]>,
+}
+
+/// Intervention applied after `step` steps, `nodes` are the affected nodes
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InterventionTraceFrame {
+    pub step: usize,
+    pub intervention: Intervention,
+    pub nodes: Vec<usize>,
+}
+
+/// Owned counterpart of `TraceFrame` returned by `TraceReader`
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "evt")]
+pub enum TraceRecord<NodeStateT> {
+    Start(StartTraceFrame),
+    State(StateRecord<NodeStateT>),
+    Delta(DeltaTraceFrame),
+    Summary(SummaryTraceFrame),
+    Window(WindowRecord),
+    Intervention(InterventionTraceFrame),
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct StateRecord<NodeStateT> {
+    pub step: usize,
+    pub actions: Vec<ActionId>,
+    pub states: Vec<NodeStateT>,
+    /// Generator of sequential steps after `step`
+    #[serde(default)]
+    pub rng: Option<Xoshiro256PlusPlus>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct WindowRecord {
+    pub step: usize,
+    pub actions: usize,
+    pub counts: Vec<u64>,
+}
+
+enum Output {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Output {
+    fn writer(&mut self) -> &mut dyn Write {
+        match self {
+            Output::Plain(w) => w,
+            Output::Gzip(w) => w,
+        }
+    }
+
+    fn finish(&mut self) -> std::io::Result<()> {
+        match self {
+            Output::Plain(w) => w.flush(),
+            Output::Gzip(w) => w.try_finish(),
+        }
+    }
+
+    /// Writes everything into the file; a gzip member is completed and a new one is started,
+    /// so the current file length is a point from which the trace can be continued.
+    fn checkpoint(&mut self) -> std::io::Result<()> {
+        match self {
+            Output::Plain(w) => w.flush(),
+            Output::Gzip(w) => {
+                w.try_finish()?;
+                w.get_mut().flush()?;
+                let file = w.get_ref().get_ref().try_clone()?;
+                *w = GzEncoder::new(BufWriter::new(file), Compression::fast());
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Observer writing `TraceFrame`s; node states every `report_state_step` steps (0 = never)
+/// and action counts of every window, both restricted by `TraceFilter`.
+pub struct TraceWriter {
+    output: Output,
+    format: TraceFormat,
+    report_state_step: usize,
+    filter: TraceFilter,
+    /// Actions of traced nodes in the last frame, the base for delta frames
+    last_actions: Option<Vec<ActionId>>,
+}
+
+impl TraceWriter {
+    /// Fails with `ErrorKind::InvalidInput` if `filter` refers to a node
+    /// out of the network of `node_count` nodes.
+    pub fn create(
+        path: &Path,
+        node_count: usize,
+        format: TraceFormat,
+        report_state_step: usize,
+        filter: TraceFilter,
+    ) -> std::io::Result<Self> {
+        filter.check_nodes(node_count)?;
+        Self::new(File::create(path)?, format, report_state_step, filter, true)
+    }
+
+    /// Continues an existing trace, used when a run is resumed from a checkpoint.
+    /// The trace is first truncated to `offset` (the length stored in the checkpoint),
+    /// dropping frames written after the checkpoint. A gzip trace then ends with the member
+    /// completed at the checkpoint and the resumed run appends a new member.
+    pub fn append(
+        path: &Path,
+        offset: Option<u64>,
+        node_count: usize,
+        format: TraceFormat,
+        report_state_step: usize,
+        filter: TraceFilter,
+    ) -> std::io::Result<Self> {
+        filter.check_nodes(node_count)?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        if let Some(offset) = offset {
+            file.set_len(offset)?;
+        }
+        let len = file.seek(SeekFrom::End(0))?;
+        Self::new(file, format, report_state_step, filter, len == 0)
+    }
+
+    fn new(
+        file: File,
+        format: TraceFormat,
+        report_state_step: usize,
+        filter: TraceFilter,
+        write_magic: bool,
+    ) -> std::io::Result<Self> {
+        let writer = BufWriter::new(file);
+        let mut output = match format {
+            TraceFormat::Json | TraceFormat::Binary => Output::Plain(writer),
+            TraceFormat::BinaryGzip => Output::Gzip(GzEncoder::new(writer, Compression::fast())),
+        };
+        if write_magic && format != TraceFormat::Json {
+            output.writer().write_all(BINARY_MAGIC)?;
+        }
+        Ok(TraceWriter {
+            output,
+            format,
+            report_state_step,
+            filter,
+            last_actions: None,
+        })
+    }
+
+    fn select<'a, T: Clone>(&self, values: &'a [T]) -> Cow<'a, [T]> {
+        match &self.filter.nodes {
+            Some(nodes) => Cow::Owned(nodes.iter().map(|n| values[*n].clone()).collect()),
+            None => Cow::Borrowed(values),
+        }
+    }
+
+    fn node_index(&self, position: usize) -> usize {
+        self.filter
+            .nodes
+            .as_ref()
+            .map(|nodes| nodes[position])
+            .unwrap_or(position)
+    }
+
+    fn write_state<ProcessT: Process>(&mut self, observation: &Observation<ProcessT>) {
+        let step = observation.step;
+        let actions = self.select(observation.state.last_actions());
+        let frame = match (self.filter.mode, &self.last_actions) {
+            (TraceMode::Summary, _) => {
+                let mut counts = vec![0; observation.action_counts.ncols()];
+                actions.iter().for_each(|a| counts[*a] += 1);
+                TraceFrame::<ProcessT>::Summary(SummaryTraceFrame { step, counts })
+            }
+            (TraceMode::Delta, Some(last_actions)) => {
+                let (nodes, actions) = actions
+                    .iter()
+                    .zip(last_actions)
+                    .enumerate()
+                    .filter(|(_, (a, last))| a != last)
+                    .map(|(i, (a, _))| (self.node_index(i), *a))
+                    .unzip();
+                TraceFrame::Delta(DeltaTraceFrame {
+                    step,
+                    nodes,
+                    actions,
+                })
+            }
+            (TraceMode::Full, _) | (TraceMode::Delta, None) => TraceFrame::State(StateTraceFrame {
+                step,
+                actions: actions.clone(),
+                states: self.select(observation.state.node_states()),
+                rng: Some(observation.rng),
+            }),
+        };
+        if self.filter.mode == TraceMode::Delta {
+            self.last_actions = Some(actions.into_owned());
+        }
+        self.write_frame(&frame);
+    }
+
+    fn write_frame<ProcessT: Process>(&mut self, frame: &TraceFrame<ProcessT>) {
+        let writer = self.output.writer();
+        if self.format == TraceFormat::Json {
+            writeln!(writer, "{}", serde_json::to_string(frame).unwrap()).unwrap();
+            return;
+        }
+        let (kind, payload) = match frame {
+            TraceFrame::Start(f) => (START_FRAME, bincode::serialize(f)),
+            TraceFrame::Delta(f) => (DELTA_FRAME, bincode::serialize(f)),
+            TraceFrame::Summary(f) => (SUMMARY_FRAME, bincode::serialize(f)),
+            TraceFrame::State(f) => (STATE_FRAME, bincode::serialize(f)),
+            TraceFrame::Window(f) => (WINDOW_FRAME, bincode::serialize(f)),
+            TraceFrame::Intervention(f) => (INTERVENTION_FRAME, bincode::serialize(f)),
+        };
+        let payload = payload.unwrap();
+        writer
+            .write_all(&(payload.len() as u64).to_le_bytes())
+            .unwrap();
+        writer.write_all(&[kind]).unwrap();
+        writer.write_all(&payload).unwrap();
+    }
+}
+
+impl<ProcessT: Process> Observer<ProcessT> for TraceWriter {
+    fn on_step(&mut self, observation: &Observation<ProcessT>) -> ObserverAction {
+        if observation.step == 0 {
+            self.write_frame(&TraceFrame::<ProcessT>::Start(StartTraceFrame {
+                seed: observation.seed,
+                nodes: observation.state.node_count(),
+                traced: self.filter.nodes.clone(),
+            }));
+        }
+        if self
+            .filter
+            .is_traced_step(observation.step, self.report_state_step)
+        {
+            self.write_state(observation);
+        }
+        ObserverAction::Continue
+    }
+
+    fn on_window(&mut self, observation: &Observation<ProcessT>) -> ObserverAction {
+        let counts = observation.action_counts;
+        let counts: Cow<[u64]> = match (&self.filter.nodes, self.filter.mode) {
+            (_, TraceMode::Summary) => {
+                let rows = self
+                    .filter
+                    .nodes
+                    .clone()
+                    .unwrap_or_else(|| (0..counts.nrows()).collect());
+                Cow::Owned(counts.select(Axis(0), &rows).sum_axis(Axis(0)).to_vec())
+            }
+            (Some(nodes), _) => Cow::Owned(counts.select(Axis(0), nodes).into_raw_vec()),
+            (None, _) => Cow::Borrowed(counts.as_slice().unwrap()),
+        };
+        self.write_frame(&TraceFrame::<ProcessT>::Window(WindowTraceFrame {
+            step: observation.step,
+            actions: observation.action_counts.ncols(),
+            counts,
+        }));
+        ObserverAction::Continue
+    }
+
+    fn on_intervention(
+        &mut self,
+        observation: &Observation<ProcessT>,
+        intervention: &Intervention,
+        nodes: &[usize],
+    ) {
+        self.write_frame(&TraceFrame::<ProcessT>::Intervention(
+            InterventionTraceFrame {
+                step: observation.step,
+                intervention: intervention.clone(),
+                nodes: nodes.to_vec(),
+            },
+        ));
+    }
+
+    fn on_checkpoint(&mut self, _observation: &Observation<ProcessT>) {
+        self.output.checkpoint().unwrap();
+    }
+
+    fn on_termination(&mut self, _observation: &Observation<ProcessT>, _report: &RunReport) {
+        self.output.finish().unwrap();
+    }
+}
+
+/// Reads a trace written in any `TraceFormat`, the format is detected from the file content.
+pub struct TraceReader<NodeStateT> {
+    input: Box<dyn BufRead>,
+    binary: bool,
+    _node_state: PhantomData<NodeStateT>,
+}
+
+impl<NodeStateT: DeserializeOwned> TraceReader<NodeStateT> {
+    pub fn open(path: &Path) -> std::io::Result<Self> {
+        let mut file = BufReader::new(File::open(path)?);
+        let compressed = file.fill_buf()?.starts_with(&GZIP_MAGIC);
+        let mut input: Box<dyn BufRead> = if compressed {
+            Box::new(BufReader::new(MultiGzDecoder::new(file)))
+        } else {
+            Box::new(file)
+        };
+        let binary = input.fill_buf()?.starts_with(BINARY_MAGIC);
+        if binary {
+            input.consume(BINARY_MAGIC.len());
+        }
+        Ok(TraceReader {
+            input,
+            binary,
+            _node_state: PhantomData,
+        })
+    }
+
+    fn read_json(&mut self) -> std::io::Result<Option<TraceRecord<NodeStateT>>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+        serde_json::from_str(&line)
+            .map(Some)
+            .map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+    }
+
+    fn read_binary(&mut self) -> std::io::Result<Option<TraceRecord<NodeStateT>>> {
+        if self.input.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut header = [0u8; 9];
+        self.input.read_exact(&mut header)?;
+        let len = u64::from_le_bytes(header[..8].try_into().unwrap()) as usize;
+        let mut payload = vec![0u8; len];
+        self.input.read_exact(&mut payload)?;
+        let to_io_error = |e| std::io::Error::new(ErrorKind::InvalidData, e);
+        let record = match header[8] {
+            START_FRAME => TraceRecord::Start(bincode::deserialize(&payload).map_err(to_io_error)?),
+            DELTA_FRAME => TraceRecord::Delta(bincode::deserialize(&payload).map_err(to_io_error)?),
+            SUMMARY_FRAME => {
+                TraceRecord::Summary(bincode::deserialize(&payload).map_err(to_io_error)?)
+            }
+            STATE_FRAME => TraceRecord::State(bincode::deserialize(&payload).map_err(to_io_error)?),
+            WINDOW_FRAME => {
+                TraceRecord::Window(bincode::deserialize(&payload).map_err(to_io_error)?)
+            }
+            INTERVENTION_FRAME => {
+                TraceRecord::Intervention(bincode::deserialize(&payload).map_err(to_io_error)?)
+            }
+            kind => {
+                return Err(std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("unknown trace frame {}", kind),
+                ))
+            }
+        };
+        Ok(Some(record))
+    }
+}
+
+impl<NodeStateT: DeserializeOwned> Iterator for TraceReader<NodeStateT> {
+    type Item = std::io::Result<TraceRecord<NodeStateT>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.binary {
+            self.read_binary().transpose()
+        } else {
+            self.read_json().transpose()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::games::chooser::DirectChooser;
+    use crate::games::game::{InitialAction, MatrixGame};
+    use crate::games::regret::{PlayerState, RegretMatchingProcess};
+    use crate::process::fixarray::FloatArray;
+    use crate::process::network::Network;
+    use crate::process::simulator::{Simulator, SimulatorConfig};
+    use crate::process::trace::{StartTraceFrame, TraceFormat, TraceReader, TraceRecord};
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    #[test]
+    fn test_trace_formats() {
+        let payoffs = [[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let game = RegretMatchingProcess::new(
+            MatrixGame::new(payoffs, InitialAction::Uniform),
+            DirectChooser::new(),
+        );
+        let network = Network::grid(3, 2);
+
+        for (format, name) in [
+            (TraceFormat::Json, "json"),
+            (TraceFormat::Binary, "bin"),
+            (TraceFormat::BinaryGzip, "bin.gz"),
+        ] {
+            let path = std::env::temp_dir().join(format!("pon-test-trace.{}", name));
+            let mut config = SimulatorConfig::new();
+            config.set_bootstrap_steps(100);
+            config.set_window_steps(50);
+            config.set_max_windows(4);
+            config.set_termination_threshold(0.0);
+            config.set_trace_path(&path);
+            config.set_trace_format(format);
+            config.set_report_state_step(20);
+            let final_actions = {
+                let mut simulator = Simulator::with_seed(&config, 5, &network, &game);
+                simulator.run();
+                simulator.state().last_actions().to_vec()
+            };
+
+            let records: Vec<TraceRecord<PlayerState<FloatArray<3>>>> = TraceReader::open(&path)
+                .unwrap()
+                .collect::<std::io::Result<_>>()
+                .unwrap();
+            let states: Vec<_> = records
+                .iter()
+                .filter_map(|r| match r {
+                    TraceRecord::State(s) => Some(s),
+                    _ => None,
+                })
+                .collect();
+            assert!(matches!(
+                records[0],
+                TraceRecord::Start(StartTraceFrame {
+                    seed: 5,
+                    nodes: 6,
+                    traced: None
+                })
+            ));
+            let windows: Vec<_> = records
+                .iter()
+                .filter_map(|r| match r {
+                    TraceRecord::Window(w) => Some(w),
+                    _ => None,
+                })
+                .collect();
+            assert_eq!(states.len(), 16);
+            assert_eq!(states[0].step, 0);
+            assert_eq!(states[15].step, 300);
+            assert_eq!(states[15].states.len(), 6);
+            assert_eq!(states[15].actions, final_actions);
+            assert_eq!(
+                windows.iter().map(|w| w.step).collect::<Vec<_>>(),
+                vec![100, 150, 200, 250, 300]
+            );
+            assert_eq!(windows[1].actions, 3);
+            assert_eq!(windows[1].counts.iter().sum::<u64>(), 6 * 50);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_resumed_trace() {
+        let payoffs = [[0.0, -1.0, 1.0], [1.0, 0.0, -1.0], [-1.0, 1.0, 0.0]];
+        let game = RegretMatchingProcess::new(
+            MatrixGame::new(payoffs, InitialAction::Uniform),
+            DirectChooser::new(),
+        );
+        let network = Network::grid(3, 2);
+
+        for ((format, name), killed) in [
+            (TraceFormat::Json, "json"),
+            (TraceFormat::Binary, "bin"),
+            (TraceFormat::BinaryGzip, "bin.gz"),
+        ]
+        .into_iter()
+        .flat_map(|format| [(format, false), (format, true)])
+        {
+            let path = std::env::temp_dir().join(format!("pon-test-resumed-trace.{}", name));
+            let checkpoint_path =
+                std::env::temp_dir().join(format!("pon-test-resumed-trace-{}.json", name));
+            let mut config = SimulatorConfig::new();
+            config.set_bootstrap_steps(100);
+            config.set_window_steps(50);
+            config.set_max_windows(4);
+            config.set_termination_threshold(0.0);
+            config.set_trace_path(&path);
+            config.set_trace_format(format);
+            config.set_report_state_step(10);
+            config.set_checkpoint(&checkpoint_path, 100);
+            {
+                // Stopped between the checkpoints at steps 200 and 300
+                let mut simulator = Simulator::with_seed(&config, 5, &network, &game);
+                assert_eq!(simulator.run_until(250), None);
+                if killed {
+                    // No buffers are flushed and the last frame is cut off
+                    std::mem::forget(simulator);
+                    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+                    file.write_all(&[0x1f, 0x8b, 0x08]).unwrap();
+                }
+            }
+            let mut resumed =
+                Simulator::resume(&checkpoint_path, &config, &network, &game).unwrap();
+            resumed.run();
+
+            let records: Vec<TraceRecord<PlayerState<FloatArray<3>>>> = TraceReader::open(&path)
+                .unwrap()
+                .collect::<std::io::Result<_>>()
+                .unwrap();
+            let state_steps: Vec<_> = records
+                .iter()
+                .filter_map(|r| match r {
+                    TraceRecord::State(s) => Some(s.step),
+                    _ => None,
+                })
+                .collect();
+            let window_steps: Vec<_> = records
+                .iter()
+                .filter_map(|r| match r {
+                    TraceRecord::Window(w) => Some(w.step),
+                    _ => None,
+                })
+                .collect();
+            assert!(matches!(records[0], TraceRecord::Start(_)));
+            assert_eq!(state_steps, (0..=300).step_by(10).collect::<Vec<_>>());
+            assert_eq!(window_steps, vec![100, 150, 200, 250, 300]);
+            std::fs::remove_file(path).unwrap();
+            std::fs::remove_file(checkpoint_path).unwrap();
+        }
+    }
+}
@@ -1,4 +1,6 @@
 use ndarray::Array2;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
 
 pub(crate) fn max_of_array(array: Array2<f32>) -> f32 {
     array.iter().fold(
@@ -12,3 +14,37 @@ pub(crate) fn max_of_array(array: Array2<f32>) -> f32 {
         },
     )
 }
+
+#[inline]
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+/// Creates a random stream for one node in one step that depends only on the run seed,
+/// the step and the node index, i.e. not on how nodes are distributed among threads.
+pub(crate) fn node_rng(seed: u64, step: usize, node: usize) -> SmallRng {
+    let seed = splitmix64(splitmix64(splitmix64(seed) ^ step as u64) ^ node as u64);
+    SmallRng::seed_from_u64(seed)
+}
+
+/// Seed of the `index`-th independent run derived from a master seed
+pub fn derive_seed(seed: u64, index: usize) -> u64 {
+    splitmix64(seed ^ splitmix64(index as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::process::utils::node_rng;
+    use rand::Rng;
+
+    #[test]
+    fn test_node_rng_streams_differ() {
+        // seed ^ step is the same for both pairs
+        let mut rng1 = node_rng(0, 1, 3);
+        let mut rng2 = node_rng(1, 0, 3);
+        assert_ne!(rng1.gen::<u64>(), rng2.gen::<u64>());
+    }
+}